@@ -0,0 +1,398 @@
+use video_processor::{MAX_COLOR_ADJUST, MAX_HUE_SHIFT, MIN_GAMMA, MAX_GAMMA, MAX_ASPECT_VALUE};
+use video_processor::{Interpolation, Mirror, OutputCodec, PadFill, Padding, PlaybackDirection, PresetStore, ProcessOptions, Rect, ResizeMode, RotateFlags, RotationFill};
+use std::ffi::OsStr;
+
+pub const USAGE: &str = "\
+Usage: bubbles-cli <INPUT> [OPTIONS]
+
+Options:
+  -o, --output <PATH>     Output file (default: <INPUT>_edit.<ext>)
+  -p, --preset <NAME>     Export preset, the options after it are applied on top (see --list-presets)
+  -c, --codec <CODEC>     Output codec: mp4v, h264, mjpg, xvid or ffv1 (default: mp4v)
+  -r, --rotate <DEGREES>  Clockwise rotation: 0, 90, 180 or 270
+  -s, --scale <FACTOR>    Rescale the frames (ex: 0.5)
+      --width <PIXELS>    Resize to this width, keeping the aspect ratio
+      --height <PIXELS>   Resize to this height, keeping the aspect ratio. With --width: fit within both
+      --interpolation <M> Resize filter: auto, linear, area, cubic or lanczos (default: auto)
+      --pad <W:H>         Pad the resized frames to this aspect ratio, without cutting them (ex: 9:16)
+      --pad-fill <MODE>   Background of the padding: color or blur (default: color)
+      --pad-color <RGB>   Color of the padding, as hexadecimal RRGGBB (default: 000000)
+  -a, --angle <DEGREES>   Clockwise rotation by any angle, after --rotate (ex: -2.5)
+      --angle-fill <MODE> Borders of the free rotation: expand, crop or fill (default: expand)
+      --fill-color <RGB>  Color of the borders, as hexadecimal RRGGBB (default: 000000)
+  -m, --mirror <AXIS>     Mirror the frames after rotating: h, v or hv
+      --crop <X,Y,W,H>    Crop the frames, in pixels of the input video
+      --brightness <V>    Brightness, from -100 to 100 (default: 0)
+      --contrast <V>      Contrast, from -100 to 100 (default: 0)
+      --saturation <V>    Saturation, from -100 (grey) to 100 (default: 0)
+      --gamma <V>         Gamma, from 0.2 to 5, above 1 brightens the shadows (default: 1)
+      --hue <DEGREES>     Hue shift, from -180 to 180 (default: 0)
+      --temperature <V>   White balance, from -100 (bluer) to 100 (warmer) (default: 0)
+  -q, --quality <0-100>   Encoder quality passed to OpenCV
+  -b, --bitrate <KBPS>    Target video bitrate in kbit/s (encoded by ffmpeg), not for ffv1
+      --speed <FACTOR>    Playback speed, from 0.25 (slow motion) to 16 (timelapse)
+      --blend             Average the skipped frames when speeding up
+  -d, --direction <DIR>   forward, reverse or boomerang (default: forward)
+      --start <FRAME>     First frame of the output
+      --end <FRAME>       Last frame of the output (excluded)
+      --no-audio          Do not copy the sound of the input file
+      --list-presets      Print the built-in presets & the ones saved from the editor
+  -h, --help              Print this message";
+
+#[derive(Debug)]
+pub enum CliCommand
+{
+    Help,
+    ListPresets,
+    Process(CliArgs),
+}
+
+#[derive(Debug)]
+pub struct CliArgs
+{
+    pub input_path: std::path::PathBuf,
+    pub options: ProcessOptions,
+}
+
+fn create_default_edit_path(file_name: &std::path::Path, extension: &str) -> std::path::PathBuf
+{
+    let mut new_file_stem = file_name.file_stem().unwrap_or(OsStr::new("video")).to_owned();
+    new_file_stem.push("_edit");
+
+    let mut processed_file_path = file_name.with_file_name(new_file_stem);
+    processed_file_path.set_extension(extension);
+    processed_file_path
+}
+
+fn parse_rotation(value: &str) -> Result<Option<RotateFlags>, String>
+{
+    let degrees = if value == "-90" { Some(270) } else { value.parse::<u32>().ok() };
+    degrees.and_then(video_processor::rotation_from_degrees)
+           .ok_or(format!("Invalid rotation \"{value}\": expected 0, 90, 180 or 270"))
+}
+
+fn parse_crop(value: &str) -> Result<Rect, String>
+{
+    let values = value.split(',')
+        .map(|value| value.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid crop \"{value}\": expected X,Y,WIDTH,HEIGHT"))?;
+    match values[..]
+    {
+        [x, y, width, height] if x >= 0 && y >= 0 && width > 0 && height > 0 => Ok(Rect::new(x, y, width, height)),
+        _                                                                    => Err(format!("Invalid crop \"{value}\": expected X,Y,WIDTH,HEIGHT")),
+    }
+}
+
+fn parse_mirror(value: &str) -> Result<Mirror, String>
+{
+    match value
+    {
+        "h"         => Ok(Mirror { horizontal: true, vertical: false }),
+        "v"         => Ok(Mirror { horizontal: false, vertical: true }),
+        "hv" | "vh" => Ok(Mirror { horizontal: true, vertical: true }),
+        _           => Err(format!("Invalid mirror \"{value}\": expected h, v or hv")),
+    }
+}
+
+fn parse_color(value: &str) -> Result<[u8; 3], String>
+{
+    let rgb = u32::from_str_radix(value.trim_start_matches('#'), 16)
+        .ok()
+        .filter(|_| value.trim_start_matches('#').len() == 6)
+        .ok_or(format!("Invalid color \"{value}\": expected RRGGBB"))?;
+    Ok([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+}
+
+fn parse_aspect(value: &str) -> Result<(u32, u32), String>
+{
+    let error = || format!("Invalid aspect ratio \"{value}\": expected WIDTH:HEIGHT, from 1 to {MAX_ASPECT_VALUE}");
+    let (width, height) = value.split_once(':').ok_or_else(error)?;
+    match (width.trim().parse::<u32>(), height.trim().parse::<u32>())
+    {
+        (Ok(width), Ok(height)) if Padding::is_aspect_valid((width, height)) => Ok((width, height)),
+        _                                                                    => Err(error()),
+    }
+}
+
+fn parse_size(flag: &str, value: Option<String>) -> Result<i32, String>
+{
+    let size = parse_value::<i32>(flag, value)?;
+    if size <= 0
+    {
+        return Err(format!("{flag} must be positive, got {size}"));
+    }
+    Ok(size)
+}
+
+fn parse_in_range(flag: &str, value: Option<String>, min: f64, max: f64) -> Result<f64, String>
+{
+    let value = parse_value::<f64>(flag, value)?;
+    if !(min..=max).contains(&value)
+    {
+        return Err(format!("{flag} must be between {min} and {max}, got {value}"));
+    }
+    Ok(value)
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String>
+{
+    let value = value.ok_or(format!("Missing value for {flag}"))?;
+    value.parse::<T>().map_err(|_| format!("Invalid value \"{value}\" for {flag}"))
+}
+
+impl CliArgs
+{
+    // "presets" are searched by --preset
+    pub fn parse(mut args: impl Iterator<Item = String>, presets: &PresetStore) -> Result<CliCommand, String>
+    {
+        let mut input_path  = None;
+        let mut output_path = None;
+        let mut pad_fill    = None;
+        let mut pad_color   = None;
+        let mut has_bitrate = false;
+        let mut options     = ProcessOptions
+        {
+            should_process: true,
+            preview: false,
+            keep_audio: true,
+            ..Default::default()
+        };
+
+        while let Some(arg) = args.next()
+        {
+            match arg.as_str()
+            {
+                "-h" | "--help"     => return Ok(CliCommand::Help),
+                "--list-presets"    => return Ok(CliCommand::ListPresets),
+                "-p" | "--preset"   =>
+                {
+                    let name   = parse_value::<String>(&arg, args.next())?;
+                    let preset = presets.find(&name).ok_or(format!("Unknown preset \"{name}\", see --list-presets"))?;
+                    preset.apply(&mut options).map_err(|e| e.to_string())?;
+                }
+                "-o" | "--output"   => output_path = Some(std::path::PathBuf::from(parse_value::<String>(&arg, args.next())?)),
+                "-c" | "--codec"    =>
+                {
+                    let codec_id  = parse_value::<String>(&arg, args.next())?;
+                    options.codec = OutputCodec::from_id(&codec_id).ok_or(format!("Unknown codec \"{codec_id}\""))?;
+                }
+                "-r" | "--rotate"   => options.flip = parse_rotation(&parse_value::<String>(&arg, args.next())?)?,
+                "-s" | "--scale"    =>
+                {
+                    let scale = parse_value::<f32>(&arg, args.next())?;
+                    if scale <= 0_f32
+                    {
+                        return Err(format!("Scale must be positive, got {scale}"));
+                    }
+                    options.resize.mode = ResizeMode::Scale(scale);
+                }
+                "--width"           =>
+                {
+                    let width           = parse_size(&arg, args.next())?;
+                    options.resize.mode = match options.resize.mode
+                    {
+                        ResizeMode::Height(height) | ResizeMode::Fit(_, height) => ResizeMode::Fit(width, height),
+                        _                                                       => ResizeMode::Width(width),
+                    };
+                }
+                "--height"          =>
+                {
+                    let height          = parse_size(&arg, args.next())?;
+                    options.resize.mode = match options.resize.mode
+                    {
+                        ResizeMode::Width(width) | ResizeMode::Fit(width, _) => ResizeMode::Fit(width, height),
+                        _                                                    => ResizeMode::Height(height),
+                    };
+                }
+                "--interpolation"   =>
+                {
+                    let interpolation_id         = parse_value::<String>(&arg, args.next())?;
+                    options.resize.interpolation = Interpolation::from_id(&interpolation_id).ok_or(format!("Unknown interpolation \"{interpolation_id}\""))?;
+                }
+                "--pad"             =>
+                {
+                    let aspect      = parse_aspect(&parse_value::<String>(&arg, args.next())?)?;
+                    options.padding = Some(Padding { aspect, ..options.padding.unwrap_or_default() });
+                }
+                "--pad-fill"        =>
+                {
+                    let fill_id = parse_value::<String>(&arg, args.next())?;
+                    pad_fill    = Some(PadFill::from_id(&fill_id).ok_or(format!("Unknown padding fill \"{fill_id}\""))?);
+                }
+                "--pad-color"       => pad_color = Some(parse_color(&parse_value::<String>(&arg, args.next())?)?),
+                "-a" | "--angle"    => options.free_rotation.angle = parse_value(&arg, args.next())?,
+                "--angle-fill"      =>
+                {
+                    let fill_id = parse_value::<String>(&arg, args.next())?;
+                    options.free_rotation.fill = RotationFill::from_id(&fill_id).ok_or(format!("Unknown fill \"{fill_id}\""))?;
+                }
+                "--fill-color"      => options.free_rotation.fill_color = parse_color(&parse_value::<String>(&arg, args.next())?)?,
+                "-m" | "--mirror"   => options.mirror = parse_mirror(&parse_value::<String>(&arg, args.next())?)?,
+                "--crop"            => options.crop = Some(parse_crop(&parse_value::<String>(&arg, args.next())?)?),
+                "--brightness"      => options.color_adjust.brightness = parse_in_range(&arg, args.next(), -MAX_COLOR_ADJUST, MAX_COLOR_ADJUST)?,
+                "--contrast"        => options.color_adjust.contrast = parse_in_range(&arg, args.next(), -MAX_COLOR_ADJUST, MAX_COLOR_ADJUST)?,
+                "--saturation"      => options.color_adjust.saturation = parse_in_range(&arg, args.next(), -MAX_COLOR_ADJUST, MAX_COLOR_ADJUST)?,
+                "--gamma"           => options.color_adjust.gamma = parse_in_range(&arg, args.next(), MIN_GAMMA, MAX_GAMMA)?,
+                "--hue"             => options.color_adjust.hue_shift = parse_in_range(&arg, args.next(), -MAX_HUE_SHIFT, MAX_HUE_SHIFT)?,
+                "--temperature"     => options.color_adjust.temperature = parse_in_range(&arg, args.next(), -MAX_COLOR_ADJUST, MAX_COLOR_ADJUST)?,
+                "-q" | "--quality"  => options.quality = Some(parse_value::<f64>(&arg, args.next())?.clamp(0_f64, 100_f64)),
+                "-b" | "--bitrate"  =>
+                {
+                    options.bitrate_kbps = Some(parse_value(&arg, args.next())?);
+                    has_bitrate          = true;
+                }
+                "--speed"           =>
+                {
+                    let speed = parse_value::<f64>(&arg, args.next())?;
+                    if !(video_processor::MIN_SPEED..=video_processor::MAX_SPEED).contains(&speed)
+                    {
+                        return Err(format!("Speed must be between {} and {}, got {speed}", video_processor::MIN_SPEED, video_processor::MAX_SPEED));
+                    }
+                    options.speed = speed;
+                }
+                "--blend"           => options.blend_frames = true,
+                "-d" | "--direction" =>
+                {
+                    let direction_id  = parse_value::<String>(&arg, args.next())?;
+                    options.direction = PlaybackDirection::from_id(&direction_id).ok_or(format!("Unknown direction \"{direction_id}\""))?;
+                }
+                "--start"           => options.start_frame = Some(parse_value(&arg, args.next())?),
+                "--end"             => options.end_frame = Some(parse_value(&arg, args.next())?),
+                "--no-audio"        => options.keep_audio = false,
+                _ if arg.starts_with('-') => return Err(format!("Unknown option {arg}")),
+                _ if input_path.is_none() => input_path = Some(std::path::PathBuf::from(arg)),
+                _                   => return Err(format!("Unexpected argument {arg}")),
+            }
+        }
+
+        // After all the options, so they also change the padding of a preset given later
+        if pad_fill.is_some() || pad_color.is_some()
+        {
+            let padding   = options.padding.as_mut().ok_or(String::from("--pad-fill & --pad-color require --pad (or a preset with a padding)"))?;
+            padding.fill  = pad_fill.unwrap_or(padding.fill);
+            padding.color = pad_color.unwrap_or(padding.color);
+        }
+
+        // An empty range would write a video without frames
+        let start_frame = options.start_frame.unwrap_or(0);
+        if let Some(end_frame) = options.end_frame && end_frame <= start_frame
+        {
+            return Err(format!("--end ({end_frame}) must be after --start ({start_frame})"));
+        }
+
+        // The bitrate of a preset is dropped when switching to a lossless codec
+        if options.codec.is_lossless() && options.bitrate_kbps.take().is_some() && has_bitrate
+        {
+            return Err(format!("--bitrate cannot be used with {}, which is lossless", options.codec.get_id()));
+        }
+
+        let input_path         = input_path.ok_or(String::from("Missing input file"))?;
+        options.edit_file_path = output_path.unwrap_or_else(|| create_default_edit_path(&input_path, options.codec.get_extension()));
+        Ok(CliCommand::Process(Self { input_path, options }))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, String>
+    {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()), &PresetStore::default())
+    }
+
+    fn parse_options(args: &[&str]) -> ProcessOptions
+    {
+        match parse(args)
+        {
+            Ok(CliCommand::Process(cli_args)) => cli_args.options,
+            other                             => panic!("Expected options, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn width_and_height_fit_in_any_order()
+    {
+        assert_eq!(parse_options(&["in.mp4", "--width", "1280"]).resize.mode, ResizeMode::Width(1280));
+        assert_eq!(parse_options(&["in.mp4", "--height", "720"]).resize.mode, ResizeMode::Height(720));
+        assert_eq!(parse_options(&["in.mp4", "--width", "1280", "--height", "720"]).resize.mode, ResizeMode::Fit(1280, 720));
+        assert_eq!(parse_options(&["in.mp4", "--height", "720", "--width", "1280"]).resize.mode, ResizeMode::Fit(1280, 720));
+        // The last scale or size wins
+        assert_eq!(parse_options(&["in.mp4", "--width", "1280", "--scale", "0.5"]).resize.mode, ResizeMode::Scale(0.5));
+        assert_eq!(parse_options(&["in.mp4", "--scale", "0.5", "--height", "720"]).resize.mode, ResizeMode::Height(720));
+    }
+
+    #[test]
+    fn rotations_are_clockwise_degrees()
+    {
+        assert_eq!(parse_options(&["in.mp4", "--rotate", "90"]).flip, Some(RotateFlags::ROTATE_90_CLOCKWISE));
+        assert_eq!(parse_options(&["in.mp4", "--rotate", "-90"]).flip, Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE));
+        assert_eq!(parse_options(&["in.mp4", "--rotate", "0"]).flip, None);
+        assert!(parse(&["in.mp4", "--rotate", "45"]).is_err());
+    }
+
+    #[test]
+    fn options_after_a_preset_override_it()
+    {
+        let options = parse_options(&["in.mp4", "--preset", "Web 720p", "--width", "1000", "--bitrate", "4000"]);
+        assert_eq!(options.resize.mode, ResizeMode::Fit(1000, 720));
+        assert_eq!(options.codec, OutputCodec::H264);
+        assert_eq!(options.bitrate_kbps, Some(4000));
+    }
+
+    #[test]
+    fn a_preset_overrides_the_options_before_it()
+    {
+        let options = parse_options(&["in.mp4", "--scale", "0.25", "--codec", "mjpg", "--preset", "Half size"]);
+        assert_eq!(options.resize.mode, ResizeMode::Scale(0.5));
+        assert_eq!(options.codec, OutputCodec::default());
+        assert!(parse(&["in.mp4", "--preset", "Unknown"]).is_err());
+    }
+
+    #[test]
+    fn pad_fill_requires_a_padding()
+    {
+        assert!(parse(&["in.mp4", "--pad-fill", "blur"]).is_err());
+        assert!(parse(&["in.mp4", "--pad-color", "ff0000"]).is_err());
+
+        let padding = parse_options(&["in.mp4", "--pad-fill", "blur", "--pad", "1:1"]).padding;
+        assert_eq!(padding.map(|padding| (padding.aspect, padding.fill)), Some(((1, 1), PadFill::Blur)));
+
+        // Also changes the padding of a preset
+        let padding = parse_options(&["in.mp4", "--preset", "Square 1:1", "--pad-color", "ff0000"]).padding;
+        assert_eq!(padding.map(|padding| (padding.aspect, padding.fill, padding.color)), Some(((1, 1), PadFill::Blur, [255, 0, 0])));
+    }
+
+    #[test]
+    fn pad_aspect_ratios_are_bounded()
+    {
+        assert!(parse(&["in.mp4", "--pad", "0:1"]).is_err());
+        assert!(parse(&["in.mp4", "--pad", "10000000:1"]).is_err());
+        assert!(parse(&["in.mp4", "--pad", &format!("1:{}", MAX_ASPECT_VALUE + 1)]).is_err());
+        assert_eq!(parse_options(&["in.mp4", "--pad", &format!("{MAX_ASPECT_VALUE}:1")]).padding.map(|padding| padding.aspect), Some((MAX_ASPECT_VALUE, 1)));
+    }
+
+    #[test]
+    fn lossless_codecs_have_no_bitrate()
+    {
+        assert!(parse(&["in.mp4", "--codec", "ffv1", "--bitrate", "4000"]).is_err());
+        assert!(parse(&["in.mp4", "--bitrate", "4000", "--codec", "ffv1"]).is_err());
+        // The bitrate of a preset is dropped
+        assert_eq!(parse_options(&["in.mp4", "--preset", "Web 720p", "--codec", "ffv1"]).bitrate_kbps, None);
+        assert_eq!(parse_options(&["in.mp4", "--codec", "h264", "--bitrate", "4000"]).bitrate_kbps, Some(4000));
+    }
+
+    #[test]
+    fn the_frame_range_cannot_be_empty()
+    {
+        assert!(parse(&["in.mp4", "--start", "100", "--end", "50"]).is_err());
+        assert!(parse(&["in.mp4", "--start", "100", "--end", "100"]).is_err());
+        assert!(parse(&["in.mp4", "--end", "0"]).is_err());
+
+        let options = parse_options(&["in.mp4", "--start", "100", "--end", "101"]);
+        assert_eq!((options.start_frame, options.end_frame), (Some(100), Some(101)));
+        assert_eq!(parse_options(&["in.mp4", "--start", "100"]).end_frame, None);
+    }
+}
@@ -0,0 +1,102 @@
+mod args;
+use args::{CliArgs, CliCommand, USAGE};
+
+use video_processor::{VideoProcessor, WorkerEvent, Progress, PresetStore, format_duration, get_presets_path};
+use std::io::Write;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const POLLING_INTERVAL: Duration = Duration::from_millis(200);
+
+fn main() -> ExitCode
+{
+    let presets = match PresetStore::load(get_presets_path())
+    {
+        Ok(presets) => presets,
+        Err(e)      =>
+        {
+            eprintln!("warning: {e}, only the built-in presets are available");
+            PresetStore::default()
+        }
+    };
+    let cli_args = match CliArgs::parse(std::env::args().skip(1), &presets)
+    {
+        Ok(CliCommand::Process(cli_args)) => cli_args,
+        Ok(CliCommand::Help)              =>
+        {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(CliCommand::ListPresets)       =>
+        {
+            for preset in presets.get_presets()
+            {
+                println!("{:<28} {}", preset.name, preset.get_summary());
+            }
+            return ExitCode::SUCCESS;
+        }
+        Err(e)                            =>
+        {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if !cli_args.options.codec.is_supported()
+    {
+        eprintln!("error: The installed OpenCV cannot write {}", cli_args.options.codec.get_name());
+        return ExitCode::FAILURE;
+    }
+
+    let mut app = VideoProcessor::default();
+    if let Err(e) = app.try_grab_video(&cli_args.input_path)
+    {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+    if (cli_args.options.keep_audio || cli_args.options.bitrate_kbps.is_some()) && !video_processor::is_ffmpeg_available()
+    {
+        eprintln!("warning: ffmpeg was not found in PATH, the output file will have no sound and the default bitrate");
+    }
+
+    let output_path = cli_args.options.edit_file_path.clone();
+    app.dispatch_video_process(cli_args.options);
+
+    let mut progress = Progress::default();
+    while !app.is_process_finished()
+    {
+        if let Some(progression) = app.get_progression()
+        {
+            progress = progression;
+        }
+        let remaining = progress.remaining.map_or(String::from("--:--"), format_duration);
+        // Padded, since the line is rewritten in place and can get shorter
+        let line = format!("Processing: {:5.1}% | frame {}/{} | {:.1} fps | elapsed {} | ETA {}", 
+            100_f32 * progress.get_fraction(), progress.frame, progress.total_frames, progress.fps, format_duration(progress.elapsed), remaining);
+        eprint!("\r{line:<90}");
+        let _ = std::io::stderr().flush();
+        std::thread::sleep(POLLING_INTERVAL);
+    }
+    eprintln!();
+    for message in app.take_worker_messages()
+    {
+        if let WorkerEvent::Warning(warning) = message
+        {
+            eprintln!("warning: {warning}");
+        }
+    }
+
+    match app.handle_thread_join()
+    {
+        Ok(_)  =>
+        {
+            eprintln!("Done: {}", output_path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) =>
+        {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
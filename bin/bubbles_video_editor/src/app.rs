@@ -3,26 +3,68 @@ mod edit_file;
 mod video_info; 
 mod rotation_radio; 
 mod video_modes; 
+mod range_selector; 
+mod video_preview; 
+mod log_panel; 
+mod job_list; 
+mod batch_panel; 
+mod project; 
+mod preset_selector; 
+mod resize_choice; 
 use edit_file::EditFile;
 use video_info::VidInfoGui;
 use rotation_radio::RotationRadio;
 use video_modes::VideoMode;
+use range_selector::{RangeSelector, format_timestamp};
+use video_preview::VideoPreview;
+use log_panel::LogPanel;
+use job_list::show_job_queue;
+use batch_panel::BatchPanel;
+use project::{Project, PROJECT_EXTENSION};
+use preset_selector::PresetSelector;
+use resize_choice::ResizeChoice;
+pub use log_panel::init_logger;
 
 use video_processor::VideoProcessor;
 use video_processor::ProcessOptions;
 use video_processor::RotateFlags;
+use video_processor::OutputCodec;
+use video_processor::Rect;
+use video_processor::Mirror;
+use video_processor::{FreeRotation, RotationFill};
+use video_processor::{Resize, ResizeMode, Interpolation};
+use video_processor::{Padding, PadFill, MAX_ASPECT_VALUE};
+use video_processor::{ColorAdjust, MAX_COLOR_ADJUST, MAX_HUE_SHIFT, MIN_GAMMA, MAX_GAMMA};
+use video_processor::PlaybackDirection;
+use video_processor::WorkerEvent;
+use video_processor::VideoProcessorError;
+use video_processor::{Progress, format_duration};
+use video_processor::JobQueue;
+use video_processor::ExportPreset;
 
-const RESET_PROGRESS: f32       = 0.0_f32;
 const NO_SCALE_CHANGE: f32      = 1.0_f32;
 const QUARTER_SCALE_CHANGE: f32 = 0.25_f32;
 const DOUBLE_SCALE_CHANGE: f32  = 2.0_f32;
 const HALF_SCALE_CHANGE: f32    = 0.5_f32;
+const DEFAULT_QUALITY: f64      = 75_f64;
+const DEFAULT_BITRATE_KBPS: u32 = 5000;
+const SPEED_PRESETS: [f64; 6]   = [0.25, 0.5, 1.0, 2.0, 4.0, 16.0];
+const TIMELINE_LABELS_WIDTH: f32 = 380.0; // room left for the labels & buttons next to the timeline
+const DEFAULT_STEP_FRAMES: usize = 10;
+const PROGRESS_BAR_WIDTH: f32    = 200.0;
+const JOB_REPAINT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+const SIZE_PRESETS: [(&str, i32, i32); 4] = [("720p", 1280, 720), ("1080p", 1920, 1080), ("4K", 3840, 2160), ("Vertical 1080p", 1080, 1920)];
+const DEFAULT_TARGET_WIDTH: i32  = 1920;
+const DEFAULT_TARGET_HEIGHT: i32 = 1080;
+const MAX_TARGET_SIZE: i32       = 8192;
+const ASPECT_PRESETS: [(u32, u32); 5] = [(16, 9), (9, 16), (1, 1), (4, 5), (4, 3)];
 
-const MP4_EXTENSIONS:   [&'static str; 1] = ["mp4"];
 const PLACE_HOLDER_FILELNAME: &str        = "";
 
 const PREVIEW_COLOR: egui::Color32       = egui::Color32::from_rgb(120, 255, 120); // Lightish green
 const PROCESS_VIDEO_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 255, 120);
+const ERROR_COLOR: egui::Color32         = egui::Color32::from_rgb(255, 110, 110);
 
 #[derive(PartialEq)]
 enum ProcessModes
@@ -43,16 +85,49 @@ pub struct BubblesVideoEditor
     opened_file_str: String,
 
     // helper
-    progress: f32,
-    has_tried_opening: bool, 
+    progress: Progress,
+    error_message: Option<String>, // last failure of the video processor, shown until a video is loaded
+    pending_project: Option<Project>, // its crop, trim & output path are applied once its video is opened
     
     //user choice for processing: 
     flip_choice: RotationRadio,
+    mirror: Mirror,
+    free_rotation: FreeRotation,
     process_mode: ProcessModes,
     gui_scale: f32,
     new_image_scale: f32,
+    resize_choice: ResizeChoice,
+    target_width: i32,
+    target_height: i32,
+    interpolation: Interpolation,
+    use_padding: bool,
+    padding: Padding,
+    color_adjust: ColorAdjust,
+    crop: Option<Rect>,
+    trim_range: RangeSelector,
+    speed: f64,
+    blend_frames: bool,
+    direction: PlaybackDirection,
+    keep_audio: bool,
+    has_ffmpeg: bool,
+    system_probe: Option<std::thread::JoinHandle<(bool, Vec<OutputCodec>)>>, // has_ffmpeg & supported_codecs, see "new"
+    show_preview: bool,
+    output_codec: OutputCodec,
+    supported_codecs: Vec<OutputCodec>,
+    use_quality: bool,
+    quality: f64,
+    use_bitrate: bool,
+    bitrate_kbps: u32,
     video_info_gui: VidInfoGui,
     next_video_mode: VideoMode,
+    video_preview: VideoPreview,
+    timeline_position: usize,
+    is_scrubbing: bool,
+    step_frames: usize,
+    log_panel: LogPanel,
+    job_queue: JobQueue,
+    batch_panel: BatchPanel,
+    preset_selector: PresetSelector,
 }
  
 impl Default for BubblesVideoEditor 
@@ -68,15 +143,48 @@ impl Default for BubblesVideoEditor
             edit_file: EditFile::default(),
             opened_file_str: PLACE_HOLDER_FILELNAME.to_owned(),
 
-            progress: RESET_PROGRESS,
-            has_tried_opening: false,
+            progress: Progress::default(),
+            error_message: None,
+            pending_project: None,
             
             flip_choice: RotationRadio::First(None),
+            mirror: Mirror::default(),
+            free_rotation: FreeRotation::default(),
             process_mode: ProcessModes::PreviewOnly,
             gui_scale: NO_SCALE_CHANGE,
             new_image_scale: NO_SCALE_CHANGE,
+            resize_choice: ResizeChoice::Scale,
+            target_width: DEFAULT_TARGET_WIDTH,
+            target_height: DEFAULT_TARGET_HEIGHT,
+            interpolation: Interpolation::default(),
+            use_padding: false,
+            padding: Padding::default(),
+            color_adjust: ColorAdjust::default(),
+            crop: None,
+            trim_range: RangeSelector::default(),
+            speed: 1_f64,
+            blend_frames: false,
+            direction: PlaybackDirection::default(),
+            keep_audio: true,
+            has_ffmpeg: false,
+            system_probe: None,
+            show_preview: true,
+            output_codec: OutputCodec::default(),
+            supported_codecs: OutputCodec::ALL.to_vec(), // until the probe is done
+            use_quality: false,
+            quality: DEFAULT_QUALITY,
+            use_bitrate: false,
+            bitrate_kbps: DEFAULT_BITRATE_KBPS,
             video_info_gui: VidInfoGui::default(),
             next_video_mode: VideoMode::PAUSE,
+            video_preview: VideoPreview::default(),
+            timeline_position: 0,
+            is_scrubbing: false,
+            step_frames: DEFAULT_STEP_FRAMES,
+            log_panel: LogPanel::default(),
+            job_queue: JobQueue::default(),
+            batch_panel: BatchPanel::default(),
+            preset_selector: PresetSelector::default(),
         }
     }
 }
@@ -88,10 +196,18 @@ impl BubblesVideoEditor
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self 
     {
         cc.egui_ctx.set_theme(egui::Theme::Dark);
-        Default::default()
+        // Both open files or start programs: done in the background, not to delay the first frame
+        let system_probe = std::thread::spawn(|| (video_processor::is_ffmpeg_available(), probe_codecs()));
+        let mut editor   = Self { system_probe: Some(system_probe), ..Self::default() };
+        if let Some(project) = cc.storage.and_then(|storage| eframe::get_value::<Project>(storage, eframe::APP_KEY))
+        {
+            log::info!("Restoring the last session");
+            editor.apply_project(project);
+        }
+        editor
     }
 
-    fn show_menu(ctx: &egui::Context) 
+    fn show_menu(&mut self, ctx: &egui::Context) 
     {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| 
         {
@@ -99,11 +215,24 @@ impl BubblesVideoEditor
             {
                 ui.menu_button("File", |ui| 
                 {
+                    if ui.add_enabled(!self.app.has_launched_process(), egui::Button::new("Open project…")).clicked()
+                    {
+                        self.open_project();
+                    }
+                    if ui.button("Save project…").clicked()
+                    {
+                        self.save_project();
+                    }
+                    ui.separator();
                     if ui.button("Quit").clicked() 
                     {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
+                ui.menu_button("View", |ui| 
+                {
+                    ui.checkbox(&mut self.log_panel.is_open, "Log");
+                });
                 ui.add_space(16.0);
                 // egui::widgets::global_theme_preference_buttons(ui);
             });
@@ -111,19 +240,61 @@ impl BubblesVideoEditor
     }       
 
 
+    fn open_project(&mut self)
+    {
+        let Some(path) = rfd::FileDialog::new().add_filter("Bubbles project", &[PROJECT_EXTENSION]).pick_file() else
+        {
+            return;
+        };
+        match Project::load(&path)
+        {
+            Ok(project) =>
+            {
+                log::info!("Project opened: {}", path.display());
+                self.apply_project(project);
+            }
+            Err(e)      => log::error!("{e}"),
+        }
+    }
+    fn save_project(&mut self)
+    {
+        let project      = self.get_project();
+        let default_path = project.get_default_path();
+        let mut dialog   = rfd::FileDialog::new().add_filter("Bubbles project", &[PROJECT_EXTENSION]);
+        if let Some(dir) = default_path.parent()
+        {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(name) = default_path.file_name()
+        {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+        let Some(path) = dialog.save_file() else
+        {
+            return;
+        };
+        match project.save(&path)
+        {
+            Ok(()) => log::info!("Project saved: {}", path.display()),
+            Err(e) => log::error!("{e}"),
+        }
+    }
+
     fn handle_file_opening(&mut self, ui: &mut egui::Ui)
     {
         ui.add_enabled(!self.app.has_launched_process(), 
             egui::TextEdit::singleline(&mut self.opened_file_str));
 
-        // Drag n drop ...
+        // Drag n drop: one video is opened, several videos (or a folder) go to the batch
         if !self.dropped_files.is_empty() 
         {
-            let mut file      = self.dropped_files.pop().unwrap(); 
-            if let Some(path) = file.path.take()
+            let paths: Vec<std::path::PathBuf> = self.dropped_files.drain(..).filter_map(|file| file.path).collect();
+            match paths.as_slice()
             {
-                self.file_name = Some(path)
-            }   
+                []                       => (),
+                [path] if !path.is_dir() => self.file_name = Some(path.clone()),
+                _                        => self.batch_panel.set_files(&paths),
+            }
         }
         // or file dialog!
         if ui.add_enabled(!self.app.has_launched_process(), egui::Button::new("Open file…")).clicked() 
@@ -137,31 +308,36 @@ impl BubblesVideoEditor
     
         if let Some(file_path) = self.file_name.take()
         {
-            if let Err(opencv_err) = self.app.unload_video() // release video if any
+            if let Err(e) = self.app.unload_video() // release video if any
             {
-                println!("Error Releasing video: {opencv_err}");
+                log::error!("Error Releasing video: {e}");
             };
     
             self.opened_file_str = file_path.display().to_string();
     
-            self.app.try_grab_video(&file_path);
+            self.error_message = self.app.try_grab_video(&file_path).err().map(|e| e.to_string());
             self.video_info_gui.try_update(&file_path, &self.app.video_info);
     
             if self.app.has_video()
             {
                 self.edit_file = EditFile::new(&file_path, "_edit");
+                self.edit_file.set_extension(self.output_codec.get_extension());
             }
-            self.has_tried_opening = true;
+            if let Some(infos) = &self.app.video_info
+            {
+                self.trim_range = RangeSelector::new(infos.frame_count);
+            }
+            self.crop = None;
+            self.apply_pending_project();
         }
     
         if self.app.has_video()  
         {
             ui.label("Video loaded successfully!");
-            self.has_tried_opening = false;
         }
-        else if self.has_tried_opening 
+        else if let Some(error_message) = &self.error_message 
         {
-            ui.label("Cannot open video file!");
+            ui.colored_label(ERROR_COLOR, error_message);
         }
         else  
         {
@@ -171,6 +347,12 @@ impl BubblesVideoEditor
 
     fn handle_video_edit_choice(&mut self, ui: &mut egui::Ui)
     {
+        let options = self.get_options();
+        if let Some(preset) = self.preset_selector.show(ui, &options)
+        {
+            self.apply_preset(&preset);
+        }
+
         ui.label("Rotate video:");
        
         ui.horizontal(|ui|
@@ -180,35 +362,284 @@ impl BubblesVideoEditor
             ui.radio_value(&mut self.flip_choice, RotationRadio::Third(Some(RotateFlags::ROTATE_90_CLOCKWISE)), "Rotate 90 Clockwise");
             ui.radio_value(&mut self.flip_choice, RotationRadio::Forth(Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE)), "Rotate 90 Counter Clockwise");
         });
+        ui.horizontal(|ui|
+        {
+            ui.checkbox(&mut self.mirror.horizontal, "Mirror horizontally");
+            ui.checkbox(&mut self.mirror.vertical, "Mirror vertically");
+            ui.label("(?)").on_hover_text("Mirroring is applied after the rotation.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        ui.horizontal(|ui|
+        {
+            ui.label("Free rotation:");
+            ui.add(egui::Slider::new(&mut self.free_rotation.angle, -45.0..=45.0).step_by(0.1).suffix("°"));
+            egui::ComboBox::from_id_salt("rotation_fill")
+                .selected_text(self.free_rotation.fill.get_name())
+                .show_ui(ui, |ui|
+                {
+                    for fill in RotationFill::ALL
+                    {
+                        ui.selectable_value(&mut self.free_rotation.fill, fill, fill.get_name());
+                    }
+                });
+            if self.free_rotation.fill != RotationFill::Crop
+            {
+                ui.color_edit_button_srgb(&mut self.free_rotation.fill_color);
+            }
+            ui.label("(?)").on_hover_text("Clockwise, applied after the rotation above. Useful to level a tilted horizon.").on_hover_cursor(egui::CursorIcon::Help);
+        });
 
         
         ui.horizontal(|ui|{
-            ui.label("Video frame scale"); 
-            ui.label("(?)").on_hover_text("The scale of the video compared to the original size.").on_hover_cursor(egui::CursorIcon::Help);
+            ui.label("Video frame size"); 
+            ui.label("(?)").on_hover_text("Scale: compared to the original size.\nWidth, height & fit within: the aspect ratio is kept, the video can be enlarged.\nMPEG-4, H.264 & Xvid videos always get an even width & height.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        ui.horizontal(|ui|
+        {
+            for choice in ResizeChoice::ALL
+            {
+                ui.radio_value(&mut self.resize_choice, choice, choice.get_name());
+            }
+            egui::ComboBox::from_label("Interpolation")
+                .selected_text(self.interpolation.get_name())
+                .show_ui(ui, |ui|
+                {
+                    for interpolation in Interpolation::ALL
+                    {
+                        ui.selectable_value(&mut self.interpolation, interpolation, interpolation.get_name());
+                    }
+                });
+            ui.label("(?)").on_hover_text("Auto: area when shrinking, linear when enlarging. Cubic & Lanczos are sharper but slower.\nAlso used by the preview.").on_hover_cursor(egui::CursorIcon::Help);
         });
         
-        ui.add(egui::Slider::new(&mut self.new_image_scale, 0.1..=2.0));
+        if self.resize_choice == ResizeChoice::Scale
+        {
+            ui.add(egui::Slider::new(&mut self.new_image_scale, 0.1..=2.0));
+            ui.horizontal(|ui|
+            {
+                ui.label("Scale presets:");
+                if ui.button("0.25").clicked()
+                {
+                    self.new_image_scale = QUARTER_SCALE_CHANGE;
+                }
+                if ui.button("0.5").clicked()
+                {
+                    self.new_image_scale = HALF_SCALE_CHANGE;
+                }
+                if ui.button("1.0").clicked()
+                {
+                    self.new_image_scale = NO_SCALE_CHANGE;
+                }
+                if ui.button("2.0").clicked()
+                {
+                    self.new_image_scale = DOUBLE_SCALE_CHANGE;
+                }
+            });
+        }
+        else
+        {
+            ui.horizontal(|ui|
+            {
+                if matches!(self.resize_choice, ResizeChoice::Width | ResizeChoice::Fit)
+                {
+                    ui.label("Width:");
+                    ui.add(egui::DragValue::new(&mut self.target_width).range(2..=MAX_TARGET_SIZE).suffix(" px"));
+                }
+                if matches!(self.resize_choice, ResizeChoice::Height | ResizeChoice::Fit)
+                {
+                    ui.label("Height:");
+                    ui.add(egui::DragValue::new(&mut self.target_height).range(2..=MAX_TARGET_SIZE).suffix(" px"));
+                }
+                ui.label("Presets:");
+                for (name, width, height) in SIZE_PRESETS
+                {
+                    if ui.button(name).clicked()
+                    {
+                        self.target_width  = width;
+                        self.target_height = height;
+                    }
+                }
+            });
+        }
         ui.horizontal(|ui|
         {
-            ui.label("Scale presets:");
-            if ui.button("0.25").clicked()
+            ui.checkbox(&mut self.use_padding, "Pad to aspect ratio:");
+            ui.add_enabled_ui(self.use_padding, |ui|
+            {
+                let (aspect_width, aspect_height) = &mut self.padding.aspect;
+                ui.add(egui::DragValue::new(aspect_width).range(1..=MAX_ASPECT_VALUE));
+                ui.label(":");
+                ui.add(egui::DragValue::new(aspect_height).range(1..=MAX_ASPECT_VALUE));
+                for (width, height) in ASPECT_PRESETS
+                {
+                    if ui.button(format!("{width}:{height}")).clicked()
+                    {
+                        self.padding.aspect = (width, height);
+                    }
+                }
+                for fill in PadFill::ALL
+                {
+                    ui.radio_value(&mut self.padding.fill, fill, fill.get_name());
+                }
+                if self.padding.fill == PadFill::Color
+                {
+                    ui.color_edit_button_srgb(&mut self.padding.color);
+                }
+            });
+            ui.label("(?)").on_hover_text("Letterbox or pillarbox: the resized video is centered on a frame of this aspect ratio, nothing is cut.\nUse 9:16 to post a landscape video as a vertical one.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        if let Some(infos) = &self.app.video_info
+        {
+            let frame_size = self.get_options().get_frame_sizes(infos).get_edit();
+            ui.label(format!("Output frame: {}x{}", frame_size.width, frame_size.height));
+        }
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Crop:");
+            match self.crop
+            {
+                None       => ui.label("Full frame"),
+                Some(rect) => ui.label(format!("{}x{} at ({}, {})", rect.width, rect.height, rect.x, rect.y)),
+            };
+            if ui.add_enabled(self.crop.is_some(), egui::Button::new("Reset crop")).clicked()
             {
-                self.new_image_scale = QUARTER_SCALE_CHANGE;
+                self.crop = None;
             }
-            if ui.button("0.5").clicked()
+            ui.label("(?)").on_hover_text("Drag a rectangle on the preview to crop the video. Drag again to refine the crop.\nNot available while using a free rotation.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        if let (Some(rect), Some(infos)) = (self.crop.as_mut(), &self.app.video_info)
+        {
+            let frame_size = infos.frame_size;
+            ui.horizontal(|ui|
             {
-                self.new_image_scale = HALF_SCALE_CHANGE;
+                ui.label("x:");
+                ui.add(egui::DragValue::new(&mut rect.x).range(0..=frame_size.width - 1));
+                ui.label("y:");
+                ui.add(egui::DragValue::new(&mut rect.y).range(0..=frame_size.height - 1));
+                ui.label("width:");
+                ui.add(egui::DragValue::new(&mut rect.width).range(1..=frame_size.width - rect.x));
+                ui.label("height:");
+                ui.add(egui::DragValue::new(&mut rect.height).range(1..=frame_size.height - rect.y));
+            });
+        }
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Colors");
+            if ui.add_enabled(self.color_adjust.is_adjusted(), egui::Button::new("Reset colors")).clicked()
+            {
+                self.color_adjust = ColorAdjust::default();
             }
-            if ui.button("1.0").clicked()
+            ui.label("(?)").on_hover_text("Applied after the rotation & the crop, shown live on the preview.\nGamma above 1 brightens the shadows. Temperature warms up (orange) or cools down (blue) the whites.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        egui::Grid::new("color_adjust").num_columns(2).show(ui, |ui|
+        {
+            let range = -MAX_COLOR_ADJUST..=MAX_COLOR_ADJUST;
+            ui.label("Brightness:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.brightness, range.clone()).step_by(1.0));
+            ui.end_row();
+            ui.label("Contrast:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.contrast, range.clone()).step_by(1.0));
+            ui.end_row();
+            ui.label("Saturation:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.saturation, range.clone()).step_by(1.0));
+            ui.end_row();
+            ui.label("Gamma:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.gamma, MIN_GAMMA..=MAX_GAMMA).logarithmic(true));
+            ui.end_row();
+            ui.label("Hue:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.hue_shift, -MAX_HUE_SHIFT..=MAX_HUE_SHIFT).step_by(1.0).suffix("°"));
+            ui.end_row();
+            ui.label("Temperature:");
+            ui.add(egui::Slider::new(&mut self.color_adjust.temperature, range).step_by(1.0));
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui|{
+            ui.label("Trim video"); 
+            ui.label("(?)").on_hover_text("Drag the handles to select the first and last frame of the output video.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        let fps = self.app.video_info.as_ref().map_or(0_f64, |infos| infos.fps);
+        self.trim_range.show(ui, fps);
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Direction:");
+            for direction in PlaybackDirection::ALL
             {
-                self.new_image_scale = NO_SCALE_CHANGE;
+                ui.radio_value(&mut self.direction, direction, direction.get_name());
             }
-            if ui.button("2.0").clicked()
+            ui.label("(?)").on_hover_text("Boomerang plays the video forwards then backwards.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Speed:");
+            ui.add(egui::Slider::new(&mut self.speed, video_processor::MIN_SPEED..=video_processor::MAX_SPEED).logarithmic(true).suffix("x"));
+            for preset in SPEED_PRESETS
             {
-                self.new_image_scale = DOUBLE_SCALE_CHANGE;
+                if ui.button(format!("{preset}x")).clicked()
+                {
+                    self.speed = preset;
+                }
             }
         });
         ui.horizontal(|ui|
+        {
+            ui.add_enabled(self.speed > 1_f64, egui::Checkbox::new(&mut self.blend_frames, "Blend frames"));
+            ui.label("(?)").on_hover_text("When speeding up, the skipped frames are averaged into the written ones instead of being dropped (smoother timelapse, slower export).").on_hover_cursor(egui::CursorIcon::Help);
+            let mut output_frames = self.trim_range.get_end().saturating_sub(self.trim_range.get_start());
+            if self.direction == PlaybackDirection::Boomerang
+            {
+                output_frames = (2 * output_frames).saturating_sub(1);
+            }
+            ui.label(format!("Output duration: {}", format_timestamp(output_frames, fps * self.speed)));
+        });
+
+        ui.horizontal(|ui|
+        {
+            let previous_codec = self.output_codec;
+            egui::ComboBox::from_label("Output format")
+                .selected_text(self.output_codec.get_name())
+                .show_ui(ui, |ui|
+                {
+                    for codec in &self.supported_codecs
+                    {
+                        ui.selectable_value(&mut self.output_codec, *codec, codec.get_name());
+                    }
+                });
+            ui.label("(?)").on_hover_text("Only the formats supported by the installed OpenCV are listed.").on_hover_cursor(egui::CursorIcon::Help);
+            if self.output_codec != previous_codec
+            {
+                self.edit_file.set_extension(self.output_codec.get_extension());
+            }
+        });
+        ui.horizontal(|ui|
+        {
+            ui.checkbox(&mut self.use_quality, "Quality");
+            ui.add_enabled(self.use_quality, egui::Slider::new(&mut self.quality, 0.0..=100.0));
+            ui.label("(?)").on_hover_text("Passed to the OpenCV VideoWriter. Mostly used by Motion JPEG, ignored by some codecs.").on_hover_cursor(egui::CursorIcon::Help);
+        });
+        ui.horizontal(|ui|
+        {
+            let can_set_bitrate = self.has_ffmpeg && !self.output_codec.is_lossless();
+            ui.add_enabled(can_set_bitrate, egui::Checkbox::new(&mut self.use_bitrate, "Target bitrate"));
+            ui.add_enabled(can_set_bitrate && self.use_bitrate, egui::DragValue::new(&mut self.bitrate_kbps).range(100..=100_000).suffix(" kbit/s"));
+            let hint = match (self.has_ffmpeg, self.system_probe.is_some())
+            {
+                _ if self.output_codec.is_lossless() => "A lossless format has no bitrate.",
+                (true, _)                            => "The video is written losslessly, then encoded once by ffmpeg to reach this bitrate.",
+                (false, true)                        => "Looking for ffmpeg…",
+                (false, false)                       => "ffmpeg was not found in PATH: the bitrate cannot be set.",
+            };
+            ui.label("(?)").on_hover_text(hint).on_hover_cursor(egui::CursorIcon::Help);
+        });
+        if let Some(infos) = &self.app.video_info
+        {
+            let estimated_size = self.get_options().estimate_output_size(infos);
+            ui.label(format!("Estimated output size: ~{}", format_file_size(estimated_size)));
+        }
+        ui.horizontal(|ui|
         {
             ui.label("Output path:");
             if ui.text_edit_singleline(self.edit_file.get_buffer()).lost_focus()
@@ -218,23 +649,54 @@ impl BubblesVideoEditor
                 
             if ui.button("Set output file").clicked() 
             {
-                if let Some(path) = rfd::FileDialog::new().set_directory(self.edit_file.get_dir()).set_file_name(self.edit_file.get_name()).add_filter("Video Format", &MP4_EXTENSIONS).save_file() 
+                if let Some(path) = rfd::FileDialog::new().set_directory(self.edit_file.get_dir()).set_file_name(self.edit_file.get_name()).add_filter("Video Format", &[self.output_codec.get_extension()]).save_file() 
                 {
                     self.edit_file.update_from_path(path);    
                 }
             }            
         });
+        ui.horizontal(|ui|
+        {
+            ui.add_enabled(self.has_ffmpeg, egui::Checkbox::new(&mut self.keep_audio, "Keep audio"));
+            let hint = match (self.has_ffmpeg, self.system_probe.is_some())
+            {
+                (true, _)      => "Copies the sound of the source file into the output file.",
+                (false, true)  => "Looking for ffmpeg…",
+                (false, false) => "ffmpeg was not found in PATH: the output file will have no sound.",
+            };
+            ui.label("(?)").on_hover_text(hint).on_hover_cursor(egui::CursorIcon::Help);
+        });
 
         // This will dispatch new values to the processing thread, if process is launched!
         if self.process_mode == ProcessModes::PreviewOnly
         {
             if let Err(e) = self.app.set_flip(self.flip_choice.get())
             {
-                println!("Error: {e}");
+                log::error!("{e}");
+            }
+            if let Err(e) = self.app.set_resize(self.get_resize())
+            {
+                log::error!("{e}");
+            }
+            if let Err(e) = self.app.set_crop(self.crop)
+            {
+                log::error!("{e}");
+            }
+            if let Err(e) = self.app.set_mirror(self.mirror)
+            {
+                log::error!("{e}");
+            }
+            if let Err(e) = self.app.set_free_rotation(self.free_rotation)
+            {
+                log::error!("{e}");
             }
-            if let Err(e) = self.app.set_rescale(self.new_image_scale)
+            if let Err(e) = self.app.set_padding(self.get_padding())
             {
-                println!("Error: {e}");
+                log::error!("{e}");
+            }
+            if let Err(e) = self.app.set_color_adjust(self.color_adjust)
+            {
+                log::error!("{e}");
             }
         }
     }
@@ -272,6 +734,14 @@ impl BubblesVideoEditor
                 ui.radio_value(&mut self.process_mode, ProcessModes::PreviewOnly, "Preview & edit");
                 ui.radio_value(&mut self.process_mode, ProcessModes::PreviewAndProcess, "Process & output video");
             });
+            if self.process_mode == ProcessModes::PreviewAndProcess
+            {
+                ui.horizontal(|ui|
+                {
+                    ui.checkbox(&mut self.show_preview, "Show preview while processing");
+                    ui.label("(?)").on_hover_text("Without preview the video is processed as fast as possible.").on_hover_cursor(egui::CursorIcon::Help);
+                });
+            }
         });
         
 
@@ -283,6 +753,14 @@ impl BubblesVideoEditor
                 let options = self.create_options();
                 self.app.dispatch_video_process(options);
             }
+            // Exported in the background with the current settings, see "Export queue"
+            if ui.add_enabled(self.app.has_video(), egui::Button::new("Add to queue"))
+                .on_hover_text("If another job already writes the output file, \"_edit_001\" (002...) is added to its name.")
+                .clicked()
+            {
+                let id = self.job_queue.add_job(self.app.get_file_path().to_path_buf(), self.get_options());
+                log::info!("Job {id} added");
+            }
 
             // Pause & Play button
             ui.add_enabled_ui(self.app.has_launched_process() && self.process_mode == ProcessModes::PreviewOnly, |ui|
@@ -295,7 +773,7 @@ impl BubblesVideoEditor
                         {
                             if let Err(e) = self.app.pause_video()
                             {
-                                println!("{e}");
+                                log::error!("{e}");
                             }
                             self.next_video_mode = VideoMode::PLAY;
                         }    
@@ -303,7 +781,7 @@ impl BubblesVideoEditor
                         {
                             if let Err(e) = self.app.resume_video()
                             {
-                                println!("{e}");
+                                log::error!("{e}");
                             }
                             self.next_video_mode = VideoMode::PAUSE;
                         }    
@@ -311,16 +789,31 @@ impl BubblesVideoEditor
                 }
             });
 
+            // Frame stepping, while paused
+            ui.add_enabled_ui(self.can_step(), |ui|
+            {
+                let large_step = self.step_frames as isize;
+                for (label, frames) in [(format!("-{large_step}"), -large_step), (String::from("-1"), -1), (String::from("+1"), 1), (format!("+{large_step}"), large_step)]
+                {
+                    if ui.button(label).clicked()
+                    {
+                        self.step(frames);
+                    }
+                }
+                ui.add(egui::DragValue::new(&mut self.step_frames).range(2..=1000))
+                    .on_hover_text("Frames of the large steps.\nKeyboard: Left/Right arrows to step by one frame, with Shift for large steps.");
+            });
+
             // Abort button
             if ui.add_enabled(self.app.has_launched_process(), egui::Button::new("Abort")).clicked()
             {
                 if self.app.try_abort()
                 {
-                    println!("GUI: Message sent!"); 
+                    log::info!("Abort signal sent"); 
                 }
                 else
                 {
-                    println!("Failure sending message!"); 
+                    log::error!("Failed sending the abort signal"); 
                 }
             }
             
@@ -330,21 +823,30 @@ impl BubblesVideoEditor
                 if let Some(progression) = self.app.get_progression()
                 {
                     self.progress = progression;
-                    println!("progress: {}", self.progress);
+                    log::trace!("progress: {:?}", self.progress);
                 };
-                let progress_bar = egui::ProgressBar::new(self.progress)
+                let progress_bar = egui::ProgressBar::new(self.progress.get_fraction())
+                    .desired_width(PROGRESS_BAR_WIDTH)
                     .show_percentage()
                     .animate(true);
                 ui.add(progress_bar);
+                let remaining = self.progress.remaining.map_or(String::from("--:--"), format_duration);
+                ui.label(format!("Frame {}/{} | {:.1} fps | Elapsed {} | Remaining {}", 
+                    self.progress.frame, self.progress.total_frames, self.progress.fps, format_duration(self.progress.elapsed), remaining));
             }            
         });
+
+        if self.app.has_launched_process()
+        {
+            self.show_timeline(ui);
+        }
         
         // Video GUI Scale selector
         ui.add_enabled_ui(self.app.has_video(), |ui| 
         {
             ui.horizontal(|ui|{
-                ui.label("Preview Window scale"); 
-                ui.label("(?)").on_hover_text("The scale of the preview window compared to the original video size. Does not affect output file!").on_hover_cursor(egui::CursorIcon::Help);
+                ui.label("Preview scale"); 
+                ui.label("(?)").on_hover_text("The scale of the preview compared to the original video size. Does not affect output file!").on_hover_cursor(egui::CursorIcon::Help);
             });
 
             ui.add(egui::Slider::new(&mut self.gui_scale, 0.1..=2.0));
@@ -372,44 +874,360 @@ impl BubblesVideoEditor
 
         if let Err(e) = self.app.set_gui_scale(self.gui_scale)
         {
-            println!("Error: {e}");
+            log::error!("{e}");
         }
         if self.app.has_launched_process() && self.app.is_process_finished()
         {
+            self.video_preview.clear();
             match self.app.handle_thread_join()
             {
-                Ok(progress) => println!("Thread joined successfully final progress: {}%.", progress*100_f32),
+                Ok(progress) => log::info!("Thread joined successfully final progress: {}%.", progress*100_f32),
                 Err(e)       =>
                 { 
-                    println!("OpenCV Error in second thread: {e}")
+                    // The errors returned by the worker are logged from its events, below
+                    if matches!(e, VideoProcessorError::ThreadFailed(_))
+                    {
+                        log::error!("{e}");
+                    }
+                    self.error_message = Some(e.to_string());
                 },
             } 
         }
+        // After joining: the last messages are received by "handle_thread_join"
+        for message in self.app.take_worker_messages()
+        {
+            match message
+            {
+                WorkerEvent::Warning(warning) => log::warn!("{warning}"),
+                WorkerEvent::Error(error)     => log::error!("Error in second thread: {error}"),
+                _                             => (),
+            }
+        }
+    }
+
+    fn can_step(&self) -> bool
+    {
+        let is_paused = matches!(self.next_video_mode, VideoMode::Play(_));
+        self.app.has_launched_process() && self.process_mode == ProcessModes::PreviewOnly && is_paused
+    }
+    fn step(&self, frames: isize)
+    {
+        if let Err(e) = self.app.step_frames(frames)
+        {
+            log::error!("{e}");
+        }
+    }
+    // Arrow keys, unless a widget (ex: text field) uses them
+    fn handle_step_keys(&self, ctx: &egui::Context)
+    {
+        if !self.can_step() || ctx.memory(|memory| memory.focused().is_some())
+        {
+            return;
+        }
+        let frames = ctx.input(|input|
+        {
+            let step = if input.modifiers.shift { self.step_frames as isize } else { 1 };
+            match (input.key_pressed(egui::Key::ArrowLeft), input.key_pressed(egui::Key::ArrowRight))
+            {
+                (true, false) => Some(-step),
+                (false, true) => Some(step),
+                _             => None,
+            }
+        });
+        if let Some(frames) = frames
+        {
+            self.step(frames);
+        }
+    }
+
+    // Current position of the worker thread, which can be moved while previewing
+    fn show_timeline(&mut self, ui: &mut egui::Ui)
+    {
+        let Some(infos) = &self.app.video_info else
+        {
+            return;
+        };
+        let fps = infos.fps;
+        // The worker only seeks within the trim, so the slider does too
+        let first_frame = self.trim_range.get_start();
+        let last_frame  = self.trim_range.get_end().saturating_sub(1).max(first_frame);
+
+        // While dragging, the positions sent by the worker would make the slider jump back
+        if let Some(position) = self.app.get_position() && !self.is_scrubbing
+        {
+            self.timeline_position = position;
+        }
+        // "Set start" & "Set end" use the frame shown by the slider
+        self.timeline_position = self.timeline_position.clamp(first_frame, last_frame);
+        ui.horizontal(|ui|
+        {
+            let can_seek = self.process_mode == ProcessModes::PreviewOnly;
+            ui.label("Position:");
+            ui.spacing_mut().slider_width = (ui.available_width() - TIMELINE_LABELS_WIDTH).max(100.0);
+            let response = ui.add_enabled(can_seek, egui::Slider::new(&mut self.timeline_position, first_frame..=last_frame).show_value(false));
+            self.is_scrubbing = response.dragged();
+            if response.changed()
+            {
+                if let Err(e) = self.app.seek_to_frame(self.timeline_position)
+                {
+                    log::error!("{e}");
+                }
+            }
+            ui.label(format!("{} (frame {})", format_timestamp(self.timeline_position, fps), self.timeline_position));
+            if ui.add_enabled(can_seek, egui::Button::new("Set start")).on_hover_text("Use the current frame as the first frame of the output.").clicked()
+            {
+                self.trim_range.set_start(self.timeline_position);
+            }
+            if ui.add_enabled(can_seek, egui::Button::new("Set end")).on_hover_text("Use the current frame as the last frame of the output.").clicked()
+            {
+                self.trim_range.set_end(self.timeline_position + 1);
+            }
+        });
     }
 
     fn create_options(&mut self) -> ProcessOptions
     {
-        let edit_file_path = self.edit_file.get_path().clone();
-        let re_scale       = match self.new_image_scale
+        let options   = self.get_options();
+        self.progress = Progress::default();
+        options
+    }
+
+    // The current edits, see "apply_project"
+    fn get_project(&self) -> Project
+    {
+        let has_video   = self.app.has_video();
+        let mut options = self.get_options();
+        // Saved as chosen, even while ffmpeg is missing or still looked for
+        options.keep_audio   = self.keep_audio;
+        options.bitrate_kbps = self.use_bitrate.then_some(self.bitrate_kbps);
+        let trim = match self.trim_range.is_full_range()
+        {
+            true  => None,
+            false => Some((self.trim_range.get_start(), self.trim_range.get_end())),
+        };
+        Project
+        {
+            source_path: has_video.then(|| self.app.get_file_path().to_path_buf()),
+            output_path: has_video.then(|| self.edit_file.get_path().clone()),
+            crop: self.crop.map(|rect| [rect.x, rect.y, rect.width, rect.height]),
+            trim,
+            gui_scale: self.gui_scale,
+            output_video: self.process_mode == ProcessModes::PreviewAndProcess,
+            show_preview: self.show_preview,
+            preset: ExportPreset::from_options("", &options),
+            ..Project::default()
+        }
+    }
+    // The export options are kept if one of them is invalid. The source video is opened at the next frame
+    fn apply_project(&mut self, project: Project)
+    {
+        let mut options = self.get_options();
+        match project.preset.apply(&mut options)
+        {
+            Ok(())  => self.set_options(options),
+            Err(e)  => log::error!("The project's export options are not restored: {e}"),
+        }
+        self.gui_scale    = project.gui_scale;
+        self.show_preview = project.show_preview;
+        self.process_mode = match project.output_video
         {
-            NO_SCALE_CHANGE => None,
-            _               => Some(self.new_image_scale)
+            true  => ProcessModes::PreviewAndProcess,
+            false => ProcessModes::PreviewOnly,
         };
+        if let Some(source_path) = &project.source_path
+        {
+            self.file_name       = Some(source_path.clone());
+            self.pending_project = Some(project);
+        }
+    }
+    // The preset is applied to the current options, then the editor is updated from them
+    fn apply_preset(&mut self, preset: &ExportPreset)
+    {
+        let mut options = self.get_options();
+        if let Err(e) = preset.apply(&mut options)
+        {
+            log::error!("{e}");
+            return;
+        }
+        self.set_options(options);
+        log::info!("Preset \"{}\" applied", preset.name);
+    }
+    // The export options only: the paths, crop & trim are left as they are. The values of disabled settings are kept
+    fn set_options(&mut self, options: ProcessOptions)
+    {
+        self.flip_choice     = RotationRadio::from_flip(options.flip);
+        self.mirror          = options.mirror;
+        self.free_rotation   = options.free_rotation;
+        self.set_resize(options.resize);
+        self.use_padding     = options.padding.is_some();
+        self.padding         = options.padding.unwrap_or(self.padding);
+        self.color_adjust    = options.color_adjust;
+        self.speed           = options.speed;
+        self.blend_frames    = options.blend_frames;
+        self.direction       = options.direction;
+        self.use_quality     = options.quality.is_some();
+        self.quality         = options.quality.unwrap_or(self.quality);
+        self.use_bitrate     = options.bitrate_kbps.is_some();
+        self.bitrate_kbps    = options.bitrate_kbps.unwrap_or(self.bitrate_kbps);
+        self.keep_audio      = options.keep_audio;
+        self.set_output_codec(options.codec);
+    }
+    // Once the background probe is done, the current codec is checked
+    fn update_system_probe(&mut self, ctx: &egui::Context)
+    {
+        let Some(probe) = self.system_probe.take_if(|probe| probe.is_finished()) else
+        {
+            if self.system_probe.is_some()
+            {
+                ctx.request_repaint_after(JOB_REPAINT_INTERVAL);
+            }
+            return;
+        };
+        match probe.join()
+        {
+            Ok((has_ffmpeg, supported_codecs)) =>
+            {
+                self.has_ffmpeg       = has_ffmpeg;
+                self.supported_codecs = supported_codecs;
+            }
+            Err(_) => log::error!("The codec probe panicked, every format is listed"),
+        }
+        if let Some(&codec) = self.supported_codecs.first()
+            && !self.supported_codecs.contains(&self.output_codec)
+        {
+            log::warn!("{} is not available, using {}", self.output_codec.get_name(), codec.get_name());
+            self.set_output_codec(codec);
+        }
+    }
+    // Keeps the current codec if the installed OpenCV cannot write this one
+    fn set_output_codec(&mut self, codec: OutputCodec)
+    {
+        if !self.supported_codecs.contains(&codec)
+        {
+            log::warn!("{} is not available, keeping {}", codec.get_name(), self.output_codec.get_name());
+            return;
+        }
+        self.output_codec = codec;
+        self.edit_file.set_extension(codec.get_extension());
+    }
+    // Called once the video of the project is opened, the crop & trim are checked against it
+    fn apply_pending_project(&mut self)
+    {
+        let Some(project) = self.pending_project.take() else
+        {
+            return;
+        };
+        let Some(infos) = &self.app.video_info else
+        {
+            return; // the error is already shown
+        };
+        if let Some((start, end)) = project.trim
+        {
+            self.trim_range.set_end(end);
+            self.trim_range.set_start(start);
+        }
+        let frame_size = infos.frame_size;
+        self.crop      = project.crop
+            .map(|[x, y, width, height]| Rect::new(x, y, width, height))
+            .filter(|rect|
+            {
+                let fits = rect.x >= 0 && rect.y >= 0 && rect.width > 0 && rect.height > 0
+                    && rect.x + rect.width <= frame_size.width && rect.y + rect.height <= frame_size.height;
+                if !fits
+                {
+                    log::warn!("The crop of the project does not fit in the video, it is ignored");
+                }
+                fits
+            });
+        if let Some(output_path) = project.output_path
+        {
+            self.edit_file.update_from_path(output_path);
+        }
+    }
+
+    fn get_resize(&self) -> Resize
+    {
+        let mode = match self.resize_choice
+        {
+            ResizeChoice::Scale  => ResizeMode::Scale(self.new_image_scale),
+            ResizeChoice::Width  => ResizeMode::Width(self.target_width),
+            ResizeChoice::Height => ResizeMode::Height(self.target_height),
+            ResizeChoice::Fit    => ResizeMode::Fit(self.target_width, self.target_height),
+        };
+        Resize { mode, interpolation: self.interpolation }
+    }
+    // The values of the other modes are kept
+    fn set_resize(&mut self, resize: Resize)
+    {
+        self.resize_choice = ResizeChoice::from_mode(resize.mode);
+        self.interpolation = resize.interpolation;
+        match resize.mode
+        {
+            ResizeMode::Scale(scale)       => self.new_image_scale = scale,
+            ResizeMode::Width(width)       => self.target_width = width,
+            ResizeMode::Height(height)     => self.target_height = height,
+            ResizeMode::Fit(width, height) =>
+            {
+                self.target_width  = width;
+                self.target_height = height;
+            }
+        }
+    }
+
+    // The other values are kept while the padding is off
+    fn get_padding(&self) -> Option<Padding>
+    {
+        self.use_padding.then_some(self.padding)
+    }
+
+    fn get_options(&self) -> ProcessOptions
+    {
+        let edit_file_path = self.edit_file.get_path().clone();
+        let resize         = self.get_resize();
+        let padding        = self.get_padding();
+        let color_adjust   = self.color_adjust;
         let flip           = self.flip_choice.get();
+        let crop           = self.crop;
+        let mirror         = self.mirror;
+        let free_rotation  = self.free_rotation;
+        let speed          = self.speed;
+        let blend_frames   = self.blend_frames;
+        let direction      = self.direction;
         let should_process = self.process_mode == ProcessModes::PreviewAndProcess;
-        let preview        = true;
+        let preview        = !should_process || self.show_preview;
         let gui_scale      = self.gui_scale;
-        let options = ProcessOptions
+        let keep_audio     = self.keep_audio && self.has_ffmpeg;
+        let codec          = self.output_codec;
+        let quality        = self.use_quality.then_some(self.quality);
+        let bitrate_kbps   = (self.use_bitrate && self.has_ffmpeg && !codec.is_lossless()).then_some(self.bitrate_kbps);
+        let (start_frame, end_frame) = match self.trim_range.is_full_range()
+        {
+            true  => (None, None),
+            false => (Some(self.trim_range.get_start()), Some(self.trim_range.get_end())),
+        };
+        ProcessOptions
         {
             gui_scale,
             edit_file_path,
             flip,
             should_process,
             preview,
-            re_scale,
-        };
-        self.progress = RESET_PROGRESS;
-        options
+            resize,
+            padding,
+            start_frame,
+            end_frame,
+            keep_audio,
+            codec,
+            quality,
+            bitrate_kbps,
+            crop,
+            mirror,
+            free_rotation,
+            color_adjust,
+            speed,
+            blend_frames,
+            direction,
+        }
     }
 }
 
@@ -417,7 +1235,44 @@ impl eframe::App for BubblesVideoEditor
 {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) 
     {
-        BubblesVideoEditor::show_menu(ctx);
+        self.show_menu(ctx);
+        self.log_panel.show(ctx);
+        self.update_system_probe(ctx);
+
+        self.job_queue.update();
+        if self.job_queue.has_pending_jobs()
+        {
+            ctx.request_repaint_after(JOB_REPAINT_INTERVAL); // keep following the jobs without user input
+        }
+
+        //// Preview ////
+        if self.app.has_launched_process()
+        {
+            if let Some(frame) = self.app.get_preview_frame()
+            {
+                self.video_preview.update(ctx, frame);
+            }
+            ctx.request_repaint(); // keep fetching frames from the worker thread
+            self.handle_step_keys(ctx);
+        }
+        if !self.video_preview.is_empty()
+        {
+            egui::SidePanel::right("preview_panel").resizable(true).show(ctx, |ui|
+            {
+                ui.heading("Preview");
+                // The selection cannot be mapped back to the source frame through a free rotation
+                let allow_selection = self.process_mode == ProcessModes::PreviewOnly && !self.free_rotation.is_rotated();
+                if let Some(selection) = self.video_preview.show(ui, allow_selection)
+                    && let Some(infos) = &self.app.video_info
+                {
+                    // The padding is not part of the source frame
+                    let frame_sizes = self.get_options().get_frame_sizes(infos);
+                    let (min, max)  = (frame_sizes.unpad_normalized(selection.min), frame_sizes.unpad_normalized(selection.max));
+                    let new_crop    = video_processor::get_crop_from_selection(min, max, self.crop, infos.frame_size, self.flip_choice.get(), self.mirror);
+                    self.crop    = new_crop.or(self.crop);
+                }
+            });
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| 
         {
@@ -451,6 +1306,15 @@ impl eframe::App for BubblesVideoEditor
             ui.heading("Video Processor");
             self.handle_video_processing(ui);
 
+            ui.separator();
+            ui.heading("Batch");
+            let options = self.get_options();
+            self.batch_panel.show(ui, &mut self.job_queue, &options);
+
+            ui.separator();
+            ui.heading("Export queue");
+            show_job_queue(ui, &mut self.job_queue);
+
             if !self.app.has_launched_process()
             {
                 preview_files_being_dropped(ctx);
@@ -474,11 +1338,42 @@ impl eframe::App for BubblesVideoEditor
             ui.hyperlink_to("Source code.","https://github.com/so-groenen/bubbles_video_editor");
         });
     }
+
+    // Restored in "new"
+    fn save(&mut self, storage: &mut dyn eframe::Storage)
+    {
+        eframe::set_value(storage, eframe::APP_KEY, &self.get_project());
+    }
 }
 
 
 
 
+fn format_file_size(bytes: u64) -> String
+{
+    const KB: f64 = 1024_f64;
+    const MB: f64 = 1024_f64 * KB;
+    const GB: f64 = 1024_f64 * MB;
+    let bytes = bytes as f64;
+    match bytes
+    {
+        b if b >= GB => format!("{:.2} GB", b / GB),
+        b if b >= MB => format!("{:.1} MB", b / MB),
+        b            => format!("{:.0} KB", b / KB),
+    }
+}
+
+fn probe_codecs() -> Vec<OutputCodec>
+{
+    let supported_codecs = video_processor::probe_supported_codecs();
+    if supported_codecs.is_empty()
+    {
+        log::warn!("Codec probe failed, falling back to the default codec");
+        return vec![OutputCodec::default()];
+    }
+    supported_codecs
+}
+
 /// See: https://github.com/emilk/egui/tree/main/examples/file_dialog !
 fn preview_files_being_dropped(ctx: &egui::Context) 
 {
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+use video_processor::{BatchId, JobQueue, ProcessOptions, DEFAULT_NAME_TEMPLATE, collect_video_files};
+
+const MAX_LISTED_FILES: usize = 20; // in the tooltip of the file count
+
+// Several videos exported with the same edits, through the job queue
+pub struct BatchPanel
+{
+    files: Vec<PathBuf>,
+    name_template: String,
+    output_dir: Option<PathBuf>,
+    last_batch: Option<BatchId>,
+    is_reported: bool,
+}
+
+impl Default for BatchPanel
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            files: Vec::new(),
+            name_template: String::from(DEFAULT_NAME_TEMPLATE),
+            output_dir: None,
+            last_batch: None,
+            is_reported: false,
+        }
+    }
+}
+
+impl BatchPanel
+{
+    // Folders are replaced by the videos they contain
+    pub fn set_files(&mut self, paths: &[PathBuf])
+    {
+        self.files = collect_video_files(paths);
+        log::info!("Batch: {} videos selected", self.files.len());
+    }
+    fn add_to_queue(&mut self, job_queue: &mut JobQueue, options: &ProcessOptions)
+    {
+        let batch = job_queue.add_batch(&self.files, options, &self.name_template, self.output_dir.as_deref());
+        log::info!("Batch: {} jobs added", self.files.len());
+        self.last_batch  = Some(batch);
+        self.is_reported = false;
+        self.files.clear();
+    }
+    // "options" are the edits of the editor, applied to every file but the trim & the crop
+    pub fn show(&mut self, ui: &mut egui::Ui, job_queue: &mut JobQueue, options: &ProcessOptions)
+    {
+        ui.horizontal(|ui|
+        {
+            if ui.button("Select files…").clicked()
+                && let Some(paths) = rfd::FileDialog::new().pick_files()
+            {
+                self.set_files(&paths);
+            }
+            if ui.button("Select folder…").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                self.set_files(&[path]);
+            }
+            let file_list = self.files.iter()
+                .take(MAX_LISTED_FILES)
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.label(format!("{} videos", self.files.len())).on_hover_text(file_list);
+            if ui.add_enabled(!self.files.is_empty(), egui::Button::new("Clear")).clicked()
+            {
+                self.files.clear();
+            }
+        });
+        ui.horizontal(|ui|
+        {
+            ui.label("Output name:");
+            ui.text_edit_singleline(&mut self.name_template)
+                .on_hover_text("{name}: name of the source file, {index}: position in the batch (001, 002...).\nThe extension is the one of the output codec.");
+            ui.label("Output folder:");
+            let output_dir = self.output_dir.as_ref().map_or(String::from("Next to each video"), |dir| dir.display().to_string());
+            if ui.button(output_dir).clicked()
+                && let Some(dir) = rfd::FileDialog::new().pick_folder()
+            {
+                self.output_dir = Some(dir);
+            }
+            if self.output_dir.is_some() && ui.button("Reset").clicked()
+            {
+                self.output_dir = None;
+            }
+        });
+        let can_add = !self.files.is_empty() && !self.name_template.trim().is_empty();
+        if ui.add_enabled(can_add, egui::Button::new("Add batch to queue"))
+            .on_hover_text("Uses the current edits for every video, except the trim & the crop of the loaded video. Videos that cannot be opened are skipped.")
+            .clicked()
+        {
+            self.add_to_queue(job_queue, options);
+        }
+
+        let Some(report) = self.last_batch.and_then(|batch| job_queue.get_batch_report(batch)) else
+        {
+            return;
+        };
+        if !self.is_reported
+        {
+            log::info!("{}", report.format());
+            self.is_reported = true;
+        }
+        ui.horizontal(|ui|
+        {
+            ui.label(format!("Last batch: {}", report.get_summary()));
+            if ui.button("Copy report").clicked()
+            {
+                ui.ctx().copy_text(report.format());
+            }
+        });
+    }
+}
@@ -32,8 +32,8 @@ impl EditFile
         let extension         = file_name.extension().unwrap_or(OsStr::new("no_extension"));
         let mut new_file_stem = file_name.file_stem().unwrap_or(OsStr::new("empty_file_name")).to_owned();
 
-        println!("DEBUG: Filename {}", new_file_stem.to_str().expect("String not empty"));
-        println!("DEBUG: extension {}", extension.to_str().expect("String not empty"));
+        log::debug!("Filename {}", new_file_stem.to_str().expect("String not empty"));
+        log::debug!("Extension {}", extension.to_str().expect("String not empty"));
         new_file_stem.push(placer_holder);
         
         let mut processed_file_path = parent.join(new_file_stem);
@@ -56,7 +56,7 @@ impl EditFile
     {
         &self.edit_file_dir
     }
-    pub fn get_path(&mut self) -> &std::path::PathBuf
+    pub fn get_path(&self) -> &std::path::PathBuf
     {
         &self.edit_file_path
     }
@@ -69,6 +69,12 @@ impl EditFile
         self.edit_file_path   = std::path::PathBuf::from(&self.edit_file_buffer);
         self.edit_file_name   = self.edit_file_path.file_name().unwrap_or(OsStr::new(EditFile::DEFAULT_FILENAME)).display().to_string();
     }
+    pub fn set_extension(&mut self, extension: &str)
+    {
+        let mut path = self.edit_file_path.clone();
+        path.set_extension(extension);
+        self.update_from_path(path);
+    }
     pub fn update_from_path(&mut self, path: std::path::PathBuf)
     {
         self.edit_file_buffer = path.display().to_string();
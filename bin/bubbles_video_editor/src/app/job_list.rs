@@ -0,0 +1,85 @@
+use video_processor::{JobId, JobQueue, JobStatus, MAX_CONCURRENT_JOBS, format_duration};
+
+const JOB_LIST_HEIGHT: f32    = 180.0;
+const JOB_PROGRESS_WIDTH: f32 = 150.0;
+
+const DONE_COLOR: egui::Color32   = egui::Color32::from_rgb(120, 255, 120);
+const FAILED_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 110, 110);
+
+enum JobAction
+{
+    Cancel(JobId),
+    Retry(JobId),
+    Remove(JobId),
+}
+
+// Status, progress & buttons of every job in the queue
+pub fn show_job_queue(ui: &mut egui::Ui, job_queue: &mut JobQueue)
+{
+    ui.horizontal(|ui|
+    {
+        ui.label("Parallel jobs:");
+        ui.add(egui::DragValue::new(&mut job_queue.max_concurrent_jobs).range(1..=MAX_CONCURRENT_JOBS))
+            .on_hover_text("1 to export the jobs one after the other.");
+        ui.label(format!("{} running", job_queue.get_running_count()));
+        if ui.button("Clear finished").clicked()
+        {
+            job_queue.clear_finished();
+        }
+    });
+
+    if job_queue.get_jobs().is_empty()
+    {
+        ui.label("No jobs. Use \"Add to queue\" to export the current video with the current settings.");
+        return;
+    }
+
+    // The queue cannot change while its jobs are shown
+    let mut action = None;
+    egui::ScrollArea::vertical().max_height(JOB_LIST_HEIGHT).show(ui, |ui|
+    {
+        egui::Grid::new("job_list").striped(true).show(ui, |ui|
+        {
+            for job in job_queue.get_jobs()
+            {
+                ui.label(job.get_name()).on_hover_text(format!("From {}", job.source_path.display()));
+                match &job.status
+                {
+                    JobStatus::Done      => ui.colored_label(DONE_COLOR, job.status.get_name()),
+                    JobStatus::Failed(e) => ui.colored_label(FAILED_COLOR, job.status.get_name()).on_hover_text(e),
+                    status               => ui.label(status.get_name()),
+                };
+                ui.add(egui::ProgressBar::new(job.progress.get_fraction()).desired_width(JOB_PROGRESS_WIDTH).show_percentage());
+                match (job.status == JobStatus::Running, job.progress.remaining)
+                {
+                    (true, Some(remaining)) => ui.label(format!("{} left", format_duration(remaining))),
+                    _                       => ui.label(""),
+                };
+                ui.horizontal(|ui|
+                {
+                    if !job.status.is_finished() && ui.button("Cancel").clicked()
+                    {
+                        action = Some(JobAction::Cancel(job.id));
+                    }
+                    if matches!(job.status, JobStatus::Failed(_) | JobStatus::Cancelled) && ui.button("Retry").clicked()
+                    {
+                        action = Some(JobAction::Retry(job.id));
+                    }
+                    if job.status != JobStatus::Running && ui.button("Remove").clicked()
+                    {
+                        action = Some(JobAction::Remove(job.id));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    });
+
+    match action
+    {
+        Some(JobAction::Cancel(id)) => job_queue.cancel(id),
+        Some(JobAction::Retry(id))  => job_queue.retry(id),
+        Some(JobAction::Remove(id)) => job_queue.remove(id),
+        None                        => (),
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const MAX_LOG_RECORDS: usize        = 1000;
+const LOG_PANEL_HEIGHT: f32         = 160.0;
+const OWN_TARGETS: [&str; 2]        = ["bubbles_video_editor", "video_processor"];
+const LEVEL_FILTERS: [log::Level; 4] = [log::Level::Error, log::Level::Warn, log::Level::Info, log::Level::Debug];
+
+const ERROR_COLOR: egui::Color32   = egui::Color32::from_rgb(255, 110, 110);
+const WARNING_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 90);
+const DEBUG_COLOR: egui::Color32   = egui::Color32::GRAY;
+
+// Filled by the logger from any thread (ex: the worker thread of video_processor), read by the panel
+static LOG_RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+static ERROR_COUNT: AtomicUsize                = AtomicUsize::new(0);
+
+struct LogRecord
+{
+    level: log::Level,
+    secs: f64, // since the app started
+    target: String,
+    message: String,
+}
+
+impl LogRecord
+{
+    fn format(&self) -> String
+    {
+        format!("[{:>9.3}s] {:<5} {}: {}", self.secs, self.level.as_str(), self.target, self.message)
+    }
+}
+
+fn lock_records() -> MutexGuard<'static, VecDeque<LogRecord>>
+{
+    // A panic while holding the lock cannot leave the records half written
+    LOG_RECORDS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Everything from our crates down to debug, only the warnings & errors of the dependencies
+fn is_kept(metadata: &log::Metadata<'_>) -> bool
+{
+    let is_own    = OWN_TARGETS.iter().any(|target| metadata.target().starts_with(target));
+    let max_level = if is_own { log::Level::Debug } else { log::Level::Warn };
+    metadata.level() <= max_level
+}
+
+// Prints to stderr like env_logger (filtered by RUST_LOG), and keeps the records for the log panel
+struct GuiLogger
+{
+    stderr_logger: env_logger::Logger,
+    start: Instant,
+}
+
+impl log::Log for GuiLogger
+{
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool
+    {
+        self.stderr_logger.enabled(metadata) || is_kept(metadata)
+    }
+    fn log(&self, record: &log::Record<'_>)
+    {
+        self.stderr_logger.log(record);
+        if !is_kept(record.metadata())
+        {
+            return;
+        }
+        if record.level() == log::Level::Error
+        {
+            ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut records = lock_records();
+        if records.len() >= MAX_LOG_RECORDS
+        {
+            records.pop_front();
+        }
+        records.push_back(LogRecord
+        {
+            level: record.level(),
+            secs: self.start.elapsed().as_secs_f64(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        });
+    }
+    fn flush(&self)
+    {
+        self.stderr_logger.flush();
+    }
+}
+
+// Replaces "env_logger::init()"
+pub fn init_logger()
+{
+    let stderr_logger = env_logger::Builder::from_default_env().build();
+    let max_level     = stderr_logger.filter().max(log::LevelFilter::Debug);
+    let logger        = GuiLogger { stderr_logger, start: Instant::now() };
+    if log::set_boxed_logger(Box::new(logger)).is_ok()
+    {
+        log::set_max_level(max_level);
+    }
+}
+
+pub struct LogPanel
+{
+    pub is_open: bool,
+    min_level: log::Level,
+    seen_errors: usize,
+}
+
+impl Default for LogPanel
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            is_open: false,
+            min_level: log::Level::Info,
+            seen_errors: 0,
+        }
+    }
+}
+
+impl LogPanel
+{
+    fn get_level_color(ui: &egui::Ui, level: log::Level) -> egui::Color32
+    {
+        match level
+        {
+            log::Level::Error                    => ERROR_COLOR,
+            log::Level::Warn                     => WARNING_COLOR,
+            log::Level::Info                     => ui.visuals().text_color(),
+            log::Level::Debug | log::Level::Trace => DEBUG_COLOR,
+        }
+    }
+    // The shown records, one per line
+    fn get_text(&self) -> String
+    {
+        lock_records().iter()
+            .filter(|record| record.level <= self.min_level)
+            .map(LogRecord::format)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    // Must be called before the central panel. Opens itself when a new error is logged
+    pub fn show(&mut self, ctx: &egui::Context)
+    {
+        let error_count = ERROR_COUNT.load(Ordering::Relaxed);
+        if error_count > self.seen_errors
+        {
+            self.seen_errors = error_count;
+            self.is_open     = true;
+        }
+        if !self.is_open
+        {
+            return;
+        }
+        egui::TopBottomPanel::bottom("log_panel").resizable(true).default_height(LOG_PANEL_HEIGHT).show(ctx, |ui|
+        {
+            ui.horizontal(|ui|
+            {
+                ui.strong("Log");
+                egui::ComboBox::from_id_salt("log_level")
+                    .selected_text(self.min_level.as_str())
+                    .show_ui(ui, |ui|
+                    {
+                        for level in LEVEL_FILTERS
+                        {
+                            ui.selectable_value(&mut self.min_level, level, level.as_str());
+                        }
+                    });
+                if ui.button("Copy").on_hover_text("Copy the shown messages to the clipboard").clicked()
+                {
+                    ctx.copy_text(self.get_text());
+                }
+                if ui.button("Clear").clicked()
+                {
+                    lock_records().clear();
+                }
+                if ui.button("Close").clicked()
+                {
+                    self.is_open = false;
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().auto_shrink(false).stick_to_bottom(true).show(ui, |ui|
+            {
+                for record in lock_records().iter().filter(|record| record.level <= self.min_level)
+                {
+                    ui.colored_label(Self::get_level_color(ui, record.level), record.format());
+                }
+            });
+        });
+    }
+}
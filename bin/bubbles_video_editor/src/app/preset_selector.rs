@@ -0,0 +1,79 @@
+use video_processor::{ExportPreset, PresetStore, ProcessOptions, get_presets_path};
+
+const PRESET_NAME_WIDTH: f32 = 160.0;
+
+// Dropdown of the export presets, and the buttons to save the current options as one
+pub struct PresetSelector
+{
+    store: PresetStore,
+    name: String, // of the last chosen preset, or of the one to save
+}
+
+impl Default for PresetSelector
+{
+    fn default() -> Self
+    {
+        let store = match PresetStore::load(get_presets_path())
+        {
+            Ok(store) => store,
+            Err(e)    =>
+            {
+                // Saving would overwrite the presets of the broken file
+                log::error!("{e}. Only the built-in presets are available, and new ones cannot be saved.");
+                PresetStore::default()
+            }
+        };
+        Self { store, name: String::new() }
+    }
+}
+
+impl PresetSelector
+{
+    // Returns the chosen preset, applied by the editor. "options" are saved by "Save preset"
+    pub fn show(&mut self, ui: &mut egui::Ui, options: &ProcessOptions) -> Option<ExportPreset>
+    {
+        let mut chosen = None;
+        ui.horizontal(|ui|
+        {
+            ui.label("Preset:");
+            egui::ComboBox::from_id_salt("export_preset")
+                .selected_text("Choose…")
+                .show_ui(ui, |ui|
+                {
+                    for preset in self.store.get_presets()
+                    {
+                        if ui.selectable_label(preset.name == self.name, &preset.name).on_hover_text(preset.get_summary()).clicked()
+                        {
+                            chosen = Some(preset.clone());
+                        }
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Preset name").desired_width(PRESET_NAME_WIDTH));
+
+            let name = self.name.trim().to_owned();
+            if ui.add_enabled(self.store.can_save() && !name.is_empty(), egui::Button::new("Save preset"))
+                .on_hover_text("Saves the current export options under this name, without the crop & the trim.\nA built-in preset of the same name is replaced.")
+                .clicked()
+            {
+                match self.store.set_user_preset(ExportPreset::from_options(&name, options))
+                {
+                    Ok(()) => log::info!("Preset \"{name}\" saved"),
+                    Err(e) => log::error!("{e}"),
+                }
+            }
+            if ui.add_enabled(self.store.is_user_preset(&name), egui::Button::new("Delete preset")).clicked()
+            {
+                match self.store.remove_user_preset(&name)
+                {
+                    Ok(()) => log::info!("Preset \"{name}\" deleted"),
+                    Err(e) => log::error!("{e}"),
+                }
+            }
+        });
+        if let Some(preset) = &chosen
+        {
+            self.name.clone_from(&preset.name);
+        }
+        chosen
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use video_processor::ExportPreset;
+
+pub const PROJECT_EXTENSION: &str = "bubbles";
+const PROJECT_VERSION: u32        = 2;
+
+// Everything needed to resume an edit, saved as RON in ".bubbles" files & in the eframe storage (last session).
+// The export options are those of a preset (with an empty name), written inline next to the fields that depend
+// on the video, so the files can be read & fixed by hand. Missing fields take their default value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Project
+{
+    pub version: u32,
+    pub source_path: Option<PathBuf>,
+    pub output_path: Option<PathBuf>,
+    pub crop: Option<[i32; 4]>, // x, y, width, height in the source frame
+    pub trim: Option<(usize, usize)>, // first frame & end (excluded)
+    pub gui_scale: f32,
+    pub output_video: bool, // "Process & output video" mode
+    pub show_preview: bool,
+    #[serde(flatten)]
+    pub preset: ExportPreset,
+}
+
+impl Default for Project
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            version: PROJECT_VERSION,
+            source_path: None,
+            output_path: None,
+            crop: None,
+            trim: None,
+            gui_scale: super::NO_SCALE_CHANGE,
+            output_video: false,
+            show_preview: true,
+            preset: ExportPreset::default(),
+        }
+    }
+}
+
+impl Project
+{
+    pub fn save(&self, path: &Path) -> Result<(), String>
+    {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Cannot write project: {e}"))?;
+        std::fs::write(path, text).map_err(|e| format!("Cannot save project {}: {e}", path.display()))
+    }
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        let text    = std::fs::read_to_string(path).map_err(|e| format!("Cannot open project {}: {e}", path.display()))?;
+        let project = ron::from_str::<Project>(&text).map_err(|e| format!("Invalid project {}: {e}", path.display()))?;
+        if project.version > PROJECT_VERSION
+        {
+            log::warn!("{} was saved by a newer version, some edits may be missing", path.display());
+        }
+        Ok(project)
+    }
+    // Next to the source video, with its name
+    pub fn get_default_path(&self) -> PathBuf
+    {
+        let mut path = self.source_path.clone().unwrap_or(PathBuf::from("project"));
+        path.set_extension(PROJECT_EXTENSION);
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn the_export_options_are_written_inline()
+    {
+        let project = Project
+        {
+            source_path: Some(PathBuf::from("in.mp4")),
+            crop: Some([10, 20, 300, 400]),
+            trim: Some((5, 50)),
+            preset: ExportPreset { rotation: 90, scale: Some(0.5), pad_aspect: Some((9, 16)), ..ExportPreset::default() },
+            ..Project::default()
+        };
+        let text = ron::ser::to_string_pretty(&project, ron::ser::PrettyConfig::default()).unwrap();
+        assert!(text.contains("\"rotation\": 90"));
+        assert_eq!(ron::from_str::<Project>(&text).unwrap(), project);
+    }
+
+    #[test]
+    fn missing_fields_take_their_default_value()
+    {
+        let project = ron::from_str::<Project>("{\"rotation\": 180, \"trim\": Some((1, 2))}").unwrap();
+        assert_eq!(project.preset, ExportPreset { rotation: 180, ..ExportPreset::default() });
+        assert_eq!(project.trim, Some((1, 2)));
+        assert_eq!(project.version, PROJECT_VERSION);
+        assert!(project.show_preview);
+    }
+}
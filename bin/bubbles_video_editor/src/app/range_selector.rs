@@ -0,0 +1,143 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2};
+
+const RAIL_HEIGHT: f32   = 6.0;
+const HANDLE_RADIUS: f32 = 7.0;
+
+#[derive(PartialEq, Clone, Copy)]
+enum Handle
+{
+    Start,
+    End,
+}
+
+// Two-handle slider used to select the in/out points of the video.
+// "end" is excluded, so a full range is [0, frame_count).
+#[derive(Default)]
+pub struct RangeSelector
+{
+    start: usize,
+    end: usize,
+    max: usize,
+    dragged_handle: Option<Handle>,
+}
+
+impl RangeSelector
+{
+    pub fn new(frame_count: usize) -> Self
+    {
+        Self
+        {
+            start: 0,
+            end: frame_count,
+            max: frame_count,
+            dragged_handle: None,
+        }
+    }
+    pub fn get_start(&self) -> usize
+    {
+        self.start
+    }
+    pub fn get_end(&self) -> usize
+    {
+        self.end
+    }
+    pub fn is_full_range(&self) -> bool
+    {
+        self.start == 0 && self.end == self.max
+    }
+    pub fn set_start(&mut self, frame: usize)
+    {
+        self.move_handle(Handle::Start, frame.min(self.max));
+    }
+    pub fn set_end(&mut self, frame: usize)
+    {
+        self.move_handle(Handle::End, frame.min(self.max));
+    }
+    fn frame_to_x(&self, rect: &Rect, frame: usize) -> f32
+    {
+        if self.max == 0
+        {
+            return rect.left();
+        }
+        rect.left() + rect.width() * (frame as f32 / self.max as f32)
+    }
+    fn x_to_frame(&self, rect: &Rect, x: f32) -> usize
+    {
+        let ratio = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        (ratio * self.max as f32).round() as usize
+    }
+    fn closest_handle(&self, rect: &Rect, pointer: Pos2) -> Handle
+    {
+        let distance_to_start = (self.frame_to_x(rect, self.start) - pointer.x).abs();
+        let distance_to_end   = (self.frame_to_x(rect, self.end) - pointer.x).abs();
+        if distance_to_start <= distance_to_end { Handle::Start } else { Handle::End }
+    }
+    fn move_handle(&mut self, handle: Handle, frame: usize)
+    {
+        match handle
+        {
+            Handle::Start => self.start = frame.min(self.end),
+            Handle::End   => self.end   = frame.max(self.start),
+        }
+    }
+    pub fn show(&mut self, ui: &mut egui::Ui, fps: f64)
+    {
+        let desired_size     = Vec2::new(ui.available_width().min(400.0), 2.0 * HANDLE_RADIUS + 4.0);
+        let (rect, response) = ui.allocate_exact_size(desired_size, Sense::click_and_drag());
+        let rail             = rect.shrink2(Vec2::new(HANDLE_RADIUS, 0.0));
+
+        // The handle is picked once when the pointer goes down, so that handles do not swap while dragging
+        if let Some(pointer) = response.interact_pointer_pos()
+        {
+            let closest = self.closest_handle(&rail, pointer);
+            let handle  = *self.dragged_handle.get_or_insert(closest);
+            let frame   = self.x_to_frame(&rail, pointer.x);
+            self.move_handle(handle, frame);
+        }
+        if !response.is_pointer_button_down_on()
+        {
+            self.dragged_handle = None;
+        }
+
+        if ui.is_rect_visible(rect)
+        {
+            let visuals  = ui.style().interact(&response);
+            let painter  = ui.painter();
+            let center_y = rail.center().y;
+            let start_x  = self.frame_to_x(&rail, self.start);
+            let end_x    = self.frame_to_x(&rail, self.end);
+
+            let full_rail     = Rect::from_min_max(Pos2::new(rail.left(), center_y - RAIL_HEIGHT / 2.0), Pos2::new(rail.right(), center_y + RAIL_HEIGHT / 2.0));
+            let selected_rail = Rect::from_min_max(Pos2::new(start_x, full_rail.top()), Pos2::new(end_x, full_rail.bottom()));
+            painter.rect_filled(full_rail, RAIL_HEIGHT / 2.0, ui.visuals().widgets.inactive.bg_fill);
+            painter.rect_filled(selected_rail, RAIL_HEIGHT / 2.0, ui.visuals().selection.bg_fill);
+
+            for x in [start_x, end_x]
+            {
+                painter.circle(Pos2::new(x, center_y), HANDLE_RADIUS, visuals.bg_fill, Stroke::new(1.0, Color32::WHITE));
+            }
+        }
+
+        ui.horizontal(|ui|
+        {
+            ui.label("In:");
+            ui.add(egui::DragValue::new(&mut self.start).range(0..=self.end));
+            ui.label(format_timestamp(self.start, fps));
+            ui.label("Out:");
+            ui.add(egui::DragValue::new(&mut self.end).range(self.start..=self.max));
+            ui.label(format_timestamp(self.end, fps));
+        });
+    }
+}
+
+pub fn format_timestamp(frame: usize, fps: f64) -> String
+{
+    if fps <= 0.0
+    {
+        return String::from("--:--.--");
+    }
+    let total_secs = frame as f64 / fps;
+    let min        = (total_secs / 60.0) as u64;
+    let sec        = total_secs % 60.0;
+    format!("{:02}:{:05.2}", min, sec)
+}
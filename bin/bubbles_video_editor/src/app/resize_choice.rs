@@ -0,0 +1,37 @@
+use video_processor::ResizeMode;
+
+// The resize modes of the editor, the values of each one are kept while switching
+#[derive(PartialEq, Clone, Copy)]
+pub enum ResizeChoice
+{
+    Scale,
+    Width,
+    Height,
+    Fit,
+}
+
+impl ResizeChoice
+{
+    pub const ALL: [ResizeChoice; 4] = [ResizeChoice::Scale, ResizeChoice::Width, ResizeChoice::Height, ResizeChoice::Fit];
+
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            ResizeChoice::Scale  => "Scale",
+            ResizeChoice::Width  => "Width",
+            ResizeChoice::Height => "Height",
+            ResizeChoice::Fit    => "Fit within",
+        }
+    }
+    pub fn from_mode(mode: ResizeMode) -> Self
+    {
+        match mode
+        {
+            ResizeMode::Scale(_)  => ResizeChoice::Scale,
+            ResizeMode::Width(_)  => ResizeChoice::Width,
+            ResizeMode::Height(_) => ResizeChoice::Height,
+            ResizeMode::Fit(_, _) => ResizeChoice::Fit,
+        }
+    }
+}
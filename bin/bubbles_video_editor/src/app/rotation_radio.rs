@@ -21,4 +21,14 @@ impl RotationRadio
             Self::Forth(value)  => *value,
         }
     }    
+    pub fn from_flip(flip: Option<RotateFlags>) -> Self
+    {
+        match flip
+        {
+            None                                          => Self::First(None),
+            Some(RotateFlags::ROTATE_180)                 => Self::Second(flip),
+            Some(RotateFlags::ROTATE_90_CLOCKWISE)        => Self::Third(flip),
+            Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE) => Self::Forth(flip),
+        }
+    }
 }
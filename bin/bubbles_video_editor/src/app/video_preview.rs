@@ -0,0 +1,83 @@
+use video_processor::PreviewFrame;
+
+const PREVIEW_TEXTURE_NAME: &str = "video_preview";
+const MIN_SELECTION_SIZE: f32    = 8.0; // in points, smaller selections are treated as a click
+
+// A rectangle drawn on the preview, corners normalized to the displayed frame (0 = left/top, 1 = right/bottom)
+pub struct PreviewSelection
+{
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+// Displays the frames rendered by the worker thread as an egui texture.
+// The frames are already scaled by the worker using the "Preview scale".
+#[derive(Default)]
+pub struct VideoPreview
+{
+    texture: Option<egui::TextureHandle>,
+    drag_start: Option<egui::Pos2>,
+}
+
+impl VideoPreview
+{
+    pub fn update(&mut self, ctx: &egui::Context, frame: PreviewFrame)
+    {
+        let image = egui::ColorImage::from_rgb(frame.size, &frame.rgb);
+        match self.texture.as_mut()
+        {
+            Some(texture) => texture.set(image, egui::TextureOptions::LINEAR),
+            None          => self.texture = Some(ctx.load_texture(PREVIEW_TEXTURE_NAME, image, egui::TextureOptions::LINEAR)),
+        }
+    }
+    pub fn clear(&mut self)
+    {
+        self.texture    = None;
+        self.drag_start = None;
+    }
+    pub fn is_empty(&self) -> bool
+    {
+        self.texture.is_none()
+    }
+    // Returns the rectangle selected by dragging on the preview, once the mouse is released
+    pub fn show(&mut self, ui: &mut egui::Ui, allow_selection: bool) -> Option<PreviewSelection>
+    {
+        let texture = self.texture.as_ref()?;
+        let sense   = if allow_selection { egui::Sense::drag() } else { egui::Sense::hover() };
+
+        let response = egui::ScrollArea::both().show(ui, |ui|
+        {
+            ui.add(egui::Image::from_texture(texture).fit_to_exact_size(texture.size_vec2()).sense(sense))
+        }).inner;
+
+        if !allow_selection
+        {
+            self.drag_start = None;
+        }
+        if response.drag_started()
+        {
+            self.drag_start = response.interact_pointer_pos();
+        }
+        let drag_start   = self.drag_start?;
+        let drag_current = response.interact_pointer_pos().or(ui.ctx().pointer_latest_pos())?;
+        let selection    = egui::Rect::from_two_pos(drag_start, drag_current).intersect(response.rect);
+
+        ui.painter().rect_stroke(selection, 0.0, egui::Stroke::new(1.5, egui::Color32::YELLOW), egui::StrokeKind::Inside);
+
+        if !response.drag_stopped()
+        {
+            return None;
+        }
+        self.drag_start = None;
+        if selection.width() < MIN_SELECTION_SIZE || selection.height() < MIN_SELECTION_SIZE
+        {
+            return None;
+        }
+        let normalize = |pos: egui::Pos2|
+        {
+            let relative = (pos - response.rect.min) / response.rect.size();
+            [relative.x, relative.y]
+        };
+        Some(PreviewSelection { min: normalize(selection.min), max: normalize(selection.max) })
+    }
+}
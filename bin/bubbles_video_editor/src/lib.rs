@@ -1,4 +1,4 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
-pub use app::BubblesVideoEditor;
+pub use app::{BubblesVideoEditor, init_logger};
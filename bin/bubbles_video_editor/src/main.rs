@@ -5,12 +5,12 @@
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result 
 {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    bubbles_video_editor::init_logger(); // Log to stderr (if you run with `RUST_LOG=debug`) and to the log panel.
 
     let native_options = eframe::NativeOptions 
     {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 300.0])
+            .with_inner_size([1200.0, 800.0])
             .with_min_inner_size([300.0, 220.0])
             .with_icon(
                 // NOTE: Adding an icon is optional
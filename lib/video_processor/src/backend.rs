@@ -1,16 +1,22 @@
 pub mod helper_function;
+pub mod ffmpeg;
+pub mod codec;
+mod frame_reader;
+mod error;
+use frame_reader::FrameReader;
+pub use error::VideoProcessorError;
 use helper_function::*;
 pub use helper_function::get_video_name;
 use opencv::videoio::VideoWriter;
+use codec::OutputCodec;
 
 mod data_structures;
 pub use crate::backend::data_structures::*;
 
 use opencv::prelude::*;
-use opencv::{videoio::{self}, highgui, core::{rotate}};
+use opencv::{videoio::{self}, imgproc, core::{flip, rotate, RotateFlags, Point, Point2f, Rect, Size}};
 use std::thread::{self};
-
-const DEFAULT_FILE_NAME: &'static str = "Video Capture Edit";
+use std::time::{Duration, Instant};
 
 trait ResetUpdate
 {
@@ -24,19 +30,143 @@ impl ResetUpdate for bool
     }    
 }
 
+fn rotate_or_copy<T: opencv::core::ToInputArray + MatTraitConst>(frame: &T, result_frame: &mut Mat, rotation: Option<RotateFlags>) -> Result<(),opencv::Error>
+{
+    match rotation
+    {
+        Some(rotation) => rotate(frame, result_frame, rotation.code()),
+        None           => frame.copy_to(result_frame),
+    }
+}
+// Rotation around the center of "src_size", moved to the center of "dst_size"
+fn get_free_rotation_matrix(angle: f64, src_size: Size, dst_size: Size) -> Result<Mat,opencv::Error>
+{
+    let center     = Point2f::new(src_size.width as f32 / 2_f32, src_size.height as f32 / 2_f32);
+    let mut matrix = imgproc::get_rotation_matrix_2d(center, -angle, 1_f64)?; // OpenCV's angles are counter clockwise
+    *matrix.at_2d_mut::<f64>(0, 2)? += (dst_size.width - src_size.width) as f64 / 2_f64;
+    *matrix.at_2d_mut::<f64>(1, 2)? += (dst_size.height - src_size.height) as f64 / 2_f64;
+    Ok(matrix)
+}
+// Tones (brightness, contrast, gamma & white balance) then hue & saturation, through lookup tables
+fn adjust_colors(frame: &mut Mat, buffer: &mut Mat, color_adjust: &ColorAdjust) -> Result<(),opencv::Error>
+{
+    if color_adjust.has_tone_change()
+    {
+        opencv::core::lut(frame, &color_adjust.get_tone_table()?, buffer)?;
+        opencv::core::swap(frame, buffer)?;
+    }
+    if color_adjust.has_hsv_change()
+    {
+        imgproc::cvt_color_def(frame, buffer, imgproc::COLOR_BGR2HSV)?;
+        opencv::core::lut(buffer, &color_adjust.get_hsv_table()?, frame)?;
+        imgproc::cvt_color_def(frame, buffer, imgproc::COLOR_HSV2BGR)?;
+        opencv::core::swap(frame, buffer)?;
+    }
+    Ok(())
+}
+// Crop -> rotate -> free rotation -> mirror -> colors, "buffer" is only used by the last three
+fn transform_frame(frame: &Mat, result_frame: &mut Mat, buffer: &mut Mat, frame_sizes: &FrameSizeManager) -> Result<(),opencv::Error>
+{
+    match frame_sizes.get_crop()
+    {
+        Some(crop) => rotate_or_copy(&Mat::roi(frame, crop)?, result_frame, frame_sizes.get_rotation())?,
+        None       => rotate_or_copy(frame, result_frame, frame_sizes.get_rotation())?,
+    }
+    let free_rotation = frame_sizes.get_free_rotation();
+    if free_rotation.is_rotated()
+    {
+        let dst_size = frame_sizes.get_transformed();
+        let matrix   = get_free_rotation_matrix(free_rotation.angle, result_frame.size()?, dst_size)?;
+        imgproc::warp_affine(result_frame, buffer, &matrix, dst_size, imgproc::INTER_LINEAR, opencv::core::BORDER_CONSTANT, free_rotation.get_border_color())?;
+        opencv::core::swap(result_frame, buffer)?;
+    }
+    if let Some(flip_code) = frame_sizes.get_mirror().get_flip_code()
+    {
+        flip(result_frame, buffer, flip_code)?;
+        opencv::core::swap(result_frame, buffer)?;
+    }
+    let color_adjust = frame_sizes.get_color_adjust();
+    if color_adjust.is_adjusted()
+    {
+        adjust_colors(result_frame, buffer, &color_adjust)?;
+    }
+    Ok(())
+}
+
+const BLUR_REDUCTION: i32   = 8; // the background of the padding is blurred on a frame this many times smaller
+const BLUR_KERNEL_SIZE: i32 = 9;
+
+// Largest centered rectangle of "frame_size" with the aspect ratio of "canvas_size"
+fn get_cover_rect(frame_size: Size, canvas_size: Size) -> Rect
+{
+    let ratio = canvas_size.width as f64 / canvas_size.height as f64;
+    match (frame_size.width as f64 / frame_size.height as f64) > ratio
+    {
+        true  =>
+        {
+            let width = ((frame_size.height as f64 * ratio).round() as i32).clamp(1, frame_size.width);
+            Rect::new((frame_size.width - width) / 2, 0, width, frame_size.height)
+        }
+        false =>
+        {
+            let height = ((frame_size.width as f64 / ratio).round() as i32).clamp(1, frame_size.height);
+            Rect::new(0, (frame_size.height - height) / 2, frame_size.width, height)
+        }
+    }
+}
+// "frame" centered on a canvas of "canvas_size", which is at least as large
+fn pad_frame(frame: &Mat, result_frame: &mut Mat, canvas_size: Size, padding: &Padding) -> Result<(),opencv::Error>
+{
+    let frame_size = frame.size()?;
+    let left       = (canvas_size.width - frame_size.width) / 2;
+    let top        = (canvas_size.height - frame_size.height) / 2;
+    match padding.fill
+    {
+        PadFill::Color =>
+        {
+            let right  = canvas_size.width - frame_size.width - left;
+            let bottom = canvas_size.height - frame_size.height - top;
+            opencv::core::copy_make_border(frame, result_frame, top, bottom, left, right, opencv::core::BORDER_CONSTANT, padding.get_color())
+        }
+        PadFill::Blur  =>
+        {
+            // The frame cut to the ratio of the canvas then enlarged, blurring a small copy is much faster
+            let small_size  = Size::new((canvas_size.width / BLUR_REDUCTION).max(1), (canvas_size.height / BLUR_REDUCTION).max(1));
+            let mut small   = Mat::default();
+            let mut blurred = Mat::default();
+            imgproc::resize(&Mat::roi(frame, get_cover_rect(frame_size, canvas_size))?, &mut small, small_size, 0., 0., imgproc::INTER_AREA)?;
+            imgproc::blur(&small, &mut blurred, Size::new(BLUR_KERNEL_SIZE, BLUR_KERNEL_SIZE), Point::new(-1, -1), opencv::core::BORDER_REPLICATE)?;
+            imgproc::resize(&blurred, result_frame, canvas_size, 0., 0., imgproc::INTER_LINEAR)?;
+            frame.copy_to(&mut Mat::roi_mut(result_frame, Rect::new(left, top, frame_size.width, frame_size.height))?)
+        }
+    }
+}
+
+// None if OpenCV cannot write "codec" to "path"
+fn open_video_writer(path: &std::path::Path, codec: OutputCodec, fps: f64, frame_size: Size, params: &opencv::core::Vector<i32>) -> Result<Option<VideoWriter>, VideoProcessorError>
+{
+    let path_str = path.to_str().ok_or_else(|| VideoProcessorError::NonUtf8Path(path.to_path_buf()))?;
+    let writer   = VideoWriter::new_with_params(path_str, codec.get_fourcc_code()?, fps, frame_size, params)?;
+    Ok(writer.is_opened()?.then_some(writer))
+}
+
 trait VideoRenderer
 {
     fn pause(self: Box<Self>) -> Box<dyn VideoRenderer>;
     fn play(self: Box<Self>) -> Box<dyn VideoRenderer>;
-    fn read_capture(&mut self, capture: &mut videoio::VideoCapture) -> Result<bool,opencv::Error>;
-    fn update_frame_data(&mut self, workers: &mut WorkerThreadAsyncChannels);
+    fn read_capture(&mut self, frame_reader: &mut FrameReader) -> Result<bool,opencv::Error>;
+    fn seek(&mut self, frame_reader: &mut FrameReader, frame: usize) -> Result<(),opencv::Error>;
+    fn apply_edit(&mut self, edit: FrameEdit);
     fn update_frame(&mut self)  -> Result<(),opencv::Error> ;
-    fn update_window(&self, window: &mut HighGuiWindow)  -> Result<(),opencv::Error>;
-    fn render(&mut self, window: &HighGuiWindow)  -> Result<(),opencv::Error>;
-    fn send_progression(&self, workers: &mut WorkerThreadAsyncChannels);
+    fn update_preview(&mut self)  -> Result<(),opencv::Error> ;
+    fn render(&mut self, workers: &WorkerThreadAsyncChannels)  -> Result<(),opencv::Error>;
+    fn get_played_frames(&self) -> usize;
     fn write(&self, writer: &mut Option<VideoWriter>)  -> Result<(), opencv::Error> ;
     fn update_frame_counter(&mut self);
     fn get_progression(&self) -> f32;
+    fn has_reached_end(&self) -> bool;
+    fn is_dropped(&self) -> bool;
+    fn is_paused(&self) -> bool;
 }
 
 // NOTE: The PlayMode & PauseMode are very heavy, ideally one should use allocate on the stack using:
@@ -48,23 +178,31 @@ trait VideoRenderer
 struct PlayMode
 {
     counter: usize,
-    frame_count: usize,
+    frame_range: FrameRange,
+    frame_timing: FrameTiming,
+    output_count: usize, // how many times the current frame is written
+    frame_blender: FrameBlender,
     frame: Mat,
     result_frame: Mat,
+    rescale_buffer: Mat,
     preview_frame: Mat,
     frame_sizes: FrameSizeManager,
 }
 
 impl PlayMode
 {
-    fn new(counter: usize, frame_count: usize, frame_sizes: FrameSizeManager) -> Self
+    fn new(counter: usize, frame_range: FrameRange, frame_timing: FrameTiming, frame_sizes: FrameSizeManager) -> Self
     {
         Self 
         {
             counter,
-            frame_count,
+            frame_range,
+            frame_timing,
+            output_count: 1,
+            frame_blender: FrameBlender::default(),
             frame: Mat::default(),
             result_frame: Mat::default(),
+            rescale_buffer: Mat::default(),
             // result_frame2: None,
             preview_frame: Mat::default(), 
             frame_sizes
@@ -75,54 +213,106 @@ impl VideoRenderer for PlayMode
 {
     fn pause(self: Box<Self>) -> Box<dyn VideoRenderer>
     {
-        Box::new(PauseMode::new(self.counter, self.frame_count, self.frame_sizes, Some(self.frame)))
+        // A dropped frame was only grabbed: the paused frame is read again from the current position
+        let frame = (!self.is_dropped() && !self.frame.empty()).then_some(self.frame);
+        Box::new(PauseMode::new(self.counter, self.frame_range, self.frame_timing, self.frame_sizes, frame))
     }
     fn play(self: Box<Self>) -> Box<dyn VideoRenderer>
     {
         self
     }   
-    fn read_capture(&mut self, capture: &mut videoio::VideoCapture) -> Result<bool, opencv::Error> 
+    fn read_capture(&mut self, frame_reader: &mut FrameReader) -> Result<bool, opencv::Error> 
     {
-        capture.read(&mut self.frame)
+        self.output_count = self.frame_timing.get_output_count(self.counter);
+        if self.output_count == 0 && !self.frame_timing.blend
+        {
+            // The frame is dropped, no need to retrieve it
+            return frame_reader.grab();
+        }
+        let has_frame = frame_reader.read(&mut self.frame)?;
+        if has_frame && self.frame_timing.blend
+        {
+            self.frame_blender.add(&self.frame)?;
+            if self.output_count > 0
+            {
+                self.frame_blender.take_average(&mut self.frame)?;
+            }
+        }
+        Ok(has_frame)
+    }
+    fn seek(&mut self, frame_reader: &mut FrameReader, frame: usize) -> Result<(),opencv::Error>
+    {
+        self.counter       = frame_reader.seek(frame)?;
+        self.frame_blender = FrameBlender::default();
+        Ok(())
     }
-    fn send_progression(&self, worker_channels: &mut WorkerThreadAsyncChannels)
+    fn get_played_frames(&self) -> usize
     {
-        let progression = self.counter as f32 / self.frame_count as f32;
-        worker_channels.send_progression(progression);
+        self.counter.saturating_sub(self.frame_range.start)
     }
-    fn update_frame_data(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) 
+    fn apply_edit(&mut self, edit: FrameEdit) 
     {
-        self.frame_sizes.update_from_main(worker_channels); 
+        self.frame_sizes.apply_edit(edit); 
     }
     fn update_frame(&mut self)  -> Result<(),opencv::Error> // we update every frame all the time, therefore resize everytime 
     {
-        match self.frame_sizes.get_rotation()
+        // "frame" is kept as decoded: it becomes the paused frame, which is transformed again from the source pixels
+        let is_transformed = self.frame_sizes.is_transformed();
+        if is_transformed
         {
-            Some(rotation) => {rotate(&self.frame, &mut self.result_frame, rotation.code())?;}
-            None           => 
+            // rescale_buffer is free until the rescale below
+            transform_frame(&self.frame, &mut self.result_frame, &mut self.rescale_buffer, &self.frame_sizes)?;
+        }
+        let padding = self.frame_sizes.get_padding();
+        match (is_transformed, self.frame_sizes.is_rescaled())
+        {
+            // The writer expects frames of the rescaled size
+            (true, true)                        =>
             {
-                // Result is now stored in result_frame.
-                opencv::core::swap(&mut self.frame, &mut self.result_frame)?;
+                opencv::core::swap(&mut self.result_frame, &mut self.rescale_buffer)?;
+                opencv::imgproc::resize(&self.rescale_buffer, &mut self.result_frame, self.frame_sizes.get_resized(), 0.,0., self.frame_sizes.get_edit_interpolation())?;
+            }
+            (false, true)                       => opencv::imgproc::resize(&self.frame, &mut self.result_frame, self.frame_sizes.get_resized(), 0.,0., self.frame_sizes.get_edit_interpolation())?,
+            // Otherwise padded straight from the decoded frame below
+            (false, false) if padding.is_none() => self.frame.copy_to(&mut self.result_frame)?,
+            _                                   => (),
+        }
+        if let Some(padding) = padding
+        {
+            // Never padded in place: the canvas is larger than the frame
+            match is_transformed || self.frame_sizes.is_rescaled()
+            {
+                true  =>
+                {
+                    opencv::core::swap(&mut self.result_frame, &mut self.rescale_buffer)?;
+                    pad_frame(&self.rescale_buffer, &mut self.result_frame, self.frame_sizes.get_edit(), &padding)?;
+                }
+                false => pad_frame(&self.frame, &mut self.result_frame, self.frame_sizes.get_edit(), &padding)?,
             }
         }
-        opencv::imgproc::resize(&self.result_frame, &mut self.preview_frame, self.frame_sizes.get_preview(), 0.,0., opencv::imgproc::INTER_LINEAR)?;
         Ok(())    
     }
-    fn update_window(&self, window: &mut HighGuiWindow)  -> Result<(),opencv::Error> 
-    {  
-        window.resize(self.frame_sizes.get_preview())?;
-        Ok(())
+    fn update_preview(&mut self)  -> Result<(),opencv::Error> // only called when the preview is requested
+    {
+        let interpolation = self.frame_sizes.get_interpolation(self.result_frame.size()?, self.frame_sizes.get_preview());
+        opencv::imgproc::resize(&self.result_frame, &mut self.preview_frame, self.frame_sizes.get_preview(), 0.,0., interpolation)?;
+        Ok(())    
     }
-    fn render(&mut self, window: &HighGuiWindow) -> Result<(),opencv::Error>
+    fn render(&mut self, worker_channels: &WorkerThreadAsyncChannels) -> Result<(),opencv::Error>
     {
-        window.show(&self.preview_frame)?;
+        // If the GUI is lagging behind, the frame is dropped: the next one will be shown instead
+        worker_channels.send_preview_frame(PreviewFrame::from_bgr(&self.preview_frame)?);
         Ok(())
     }
     fn write(&self, video_writer: &mut Option<VideoWriter>)  -> Result<(), opencv::Error>
     {
         if let Some(writer) = video_writer
         {
-            writer.write(&self.result_frame)?;
+            // Slow motion: the frame is duplicated
+            for _ in 0..self.output_count
+            {
+                writer.write(&self.result_frame)?;
+            }
         }
         Ok(())
     }
@@ -132,40 +322,54 @@ impl VideoRenderer for PlayMode
     }    
     fn get_progression(&self) -> f32
     {
-        let progression = self.counter as f32 / self.frame_count as f32;
+        let progression = self.frame_range.get_progression(self.counter);
         progression
+    }
+    fn has_reached_end(&self) -> bool
+    {
+        self.frame_range.is_finished(self.counter)
+    }   
+    fn is_dropped(&self) -> bool
+    {
+        self.output_count == 0
     }   
+    fn is_paused(&self) -> bool
+    {
+        false
+    }
 }
 struct PauseMode
 {
     counter: usize,
-    frame_count: usize,
+    frame_range: FrameRange,
+    frame_timing: FrameTiming,
     frame: Option<Mat>,             
     result_frame: Mat,               
+    transform_buffer: Mat,               
     preview_frame: Mat,               
     frame_sizes: FrameSizeManager,
-    should_rotate_frame: bool,
-    should_rescale_frame: bool,
-    should_rescale_gui: bool,
-    should_update_window: bool,
+    should_transform_frame: bool,   // new frame, or new crop, rotation etc...
+    should_update_preview: bool,
+    has_pending_render: bool,
 }
 
 impl PauseMode
 {
-    fn new(counter: usize, frame_count: usize, frame_sizes: FrameSizeManager, frame: Option<Mat>) -> Self
+    fn new(counter: usize, frame_range: FrameRange, frame_timing: FrameTiming, frame_sizes: FrameSizeManager, frame: Option<Mat>) -> Self
     {
         Self
         {   
             counter,
-            frame_count,
+            frame_range,
+            frame_timing,
             frame,
             result_frame: Mat::default(),
+            transform_buffer: Mat::default(),
             preview_frame: Mat::default(),
             frame_sizes,
-            should_rotate_frame: true,
-            should_rescale_frame: true,
-            should_rescale_gui: true,
-            should_update_window: true,
+            should_transform_frame: true,
+            should_update_preview: true,
+            has_pending_render: false,
         }
     }    
 }
@@ -180,73 +384,85 @@ impl VideoRenderer for PauseMode
     }
     fn play(self: Box<Self>) -> Box<dyn VideoRenderer>
     {
-        Box::new(PlayMode::new(self.counter, self.frame_count, self.frame_sizes))
+        Box::new(PlayMode::new(self.counter, self.frame_range, self.frame_timing, self.frame_sizes))
     }   
-    fn render(&mut self, window: &HighGuiWindow) -> Result<(),opencv::Error>
+    fn render(&mut self, worker_channels: &WorkerThreadAsyncChannels) -> Result<(),opencv::Error>
     {
-        window.show(&self.preview_frame)?;
+        // The paused frame is only sent when it changed, so we retry until the GUI received it
+        if self.has_pending_render && worker_channels.send_preview_frame(PreviewFrame::from_bgr(&self.preview_frame)?)
+        {
+            self.has_pending_render.reset();
+        }
         Ok(())
     }
-    fn read_capture(&mut self, capture: &mut videoio::VideoCapture) -> Result<bool,opencv::Error> 
+    fn read_capture(&mut self, frame_reader: &mut FrameReader) -> Result<bool,opencv::Error> 
     {
         if self.frame.is_none()
         {
             let mut frame = Mat::default();
-            let result    = capture.read(&mut frame);
+            let has_frame = frame_reader.read(&mut frame)?;
             self.frame    = Some(frame);
-            return result;
+            // Like PlayMode, the counter is the next frame of the reader: playing resumes after the paused frame
+            if has_frame
+            {
+                self.counter += 1;
+            }
+            return Ok(has_frame);
         }
         Ok(true)    
     }
-    fn send_progression(&self, worker_channels: &mut WorkerThreadAsyncChannels)
+    fn seek(&mut self, frame_reader: &mut FrameReader, frame: usize) -> Result<(),opencv::Error>
     {
-        let progression = self.counter as f32 / self.frame_count as f32;
-        worker_channels.send_progression(progression);
+        // The frame at the new position is read by the next "read_capture"
+        self.counter                = frame_reader.seek(frame)?;
+        self.frame                  = None;
+        self.should_transform_frame = true;
+        self.should_update_preview  = true;
+        Ok(())
     }
-    fn update_frame_data(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) 
+    fn get_played_frames(&self) -> usize
     {
-        if self.frame_sizes.update_flip(worker_channels)
-        {
-            self.should_rotate_frame = true;
-        }
-        if self.frame_sizes.update_gui_size(worker_channels)
-        {
-            self.should_rescale_gui = true;
-        }
-        if self.frame_sizes.update_rescale(worker_channels)
-        {
-            self.should_rescale_frame = true;
-        }
-        // "should_update_window" will be set to true on the first iteration
-        // then it will be set to false when should_rescale_frame etc will be also set to false
-        // "should_update_window" is true only if requested by a new update, which will rescale, rotatate etc...
-        self.should_update_window = self.should_rescale_frame || self.should_rescale_gui || self.should_rotate_frame;
+        self.counter.saturating_sub(self.frame_range.start)
     }
-    fn update_window(&self, window: &mut HighGuiWindow)  -> Result<(),opencv::Error> 
+    fn apply_edit(&mut self, edit: FrameEdit) 
     {
-        if self.should_update_window                         // is set by update_frame_data
+        self.frame_sizes.apply_edit(edit);
+        // Rescaling only changes the size of the preview, which is resized from the transformed frame
+        if edit.is_transform()
         {
-            window.resize(self.frame_sizes.get_preview())?;
+            self.should_transform_frame = true;
         }
-        Ok(())
+        self.should_update_preview = true;
     }
     fn update_frame(&mut self) -> Result<(),opencv::Error>  
     {                                                        
-        if self.should_rotate_frame
+        if self.should_transform_frame
         {
-            match self.frame_sizes.get_rotation()
-            {
-                Some(rotation) => {rotate(&self.frame.as_ref().unwrap(), &mut self.result_frame, rotation.code())?;}
-                None           => {self.result_frame = self.frame.as_ref().unwrap().clone();}
-            } 
+            transform_frame(self.frame.as_ref().unwrap(), &mut self.result_frame, &mut self.transform_buffer, &self.frame_sizes)?;
         }
-        if self.should_rescale_gui || self.should_rotate_frame || self.should_rescale_frame
+        self.should_transform_frame.reset();
+        Ok(())
+    }
+    fn update_preview(&mut self) -> Result<(),opencv::Error>  
+    {
+        if self.should_update_preview                        // is set by apply_edit & seek
         {
-            opencv::imgproc::resize(&self.result_frame, &mut self.preview_frame, self.frame_sizes.get_preview(), 0.,0., opencv::imgproc::INTER_LINEAR)?;
+            // The edited frame is never made while paused: the preview is resized then padded from the transformed frame
+            let content_size  = self.frame_sizes.get_preview_content();
+            let interpolation = self.frame_sizes.get_interpolation(self.result_frame.size()?, content_size);
+            match self.frame_sizes.get_padding()
+            {
+                None          => opencv::imgproc::resize(&self.result_frame, &mut self.preview_frame, content_size, 0.,0., interpolation)?,
+                Some(padding) =>
+                {
+                    // transform_buffer is free until the next transform
+                    opencv::imgproc::resize(&self.result_frame, &mut self.transform_buffer, content_size, 0.,0., interpolation)?;
+                    pad_frame(&self.transform_buffer, &mut self.preview_frame, self.frame_sizes.get_preview(), &padding)?;
+                }
+            }
+            self.should_update_preview.reset();
+            self.has_pending_render = true;
         }
-        self.should_rescale_gui.reset();
-        self.should_rescale_frame.reset();
-        self.should_rotate_frame.reset();
         Ok(())
     }
 
@@ -256,73 +472,200 @@ impl VideoRenderer for PauseMode
     }
     fn get_progression(&self) -> f32
     {
-        let progression = self.counter as f32 / self.frame_count as f32;
+        let progression = self.frame_range.get_progression(self.counter);
         progression
     }
+    // Stays open on the last frame, until played or aborted
+    fn has_reached_end(&self) -> bool
+    {
+        false
+    }
+    fn is_dropped(&self) -> bool
+    {
+        false
+    }
+    fn is_paused(&self) -> bool
+    {
+        true
+    }
 }
 
  
-pub fn process_video_thread(mut capture: videoio::VideoCapture, 
+pub fn process_video_thread(capture: videoio::VideoCapture, 
+                            source_file_path: std::path::PathBuf,
                             options: ProcessOptions,
                             thread_pool: &mut VideoProcThreadPool,
-                            mut worker_channels: WorkerThreadAsyncChannels)
+                            worker_channels: WorkerThreadAsyncChannels)
+{
+    thread_pool.push(spawn_video_process(capture, source_file_path, options, worker_channels));
+}
+
+pub fn spawn_video_process(capture: videoio::VideoCapture, 
+                           source_file_path: std::path::PathBuf,
+                           options: ProcessOptions,
+                           worker_channels: WorkerThreadAsyncChannels) -> VideoProcJoinHandle
 {
-    let handle = thread::spawn(move ||
+    thread::spawn(move ||
     {
-        let video_info        = VideoInfo::new(&capture)?;
-        let total_frame_count = video_info.frame_count;
+        let result = run_video_process(capture, &source_file_path, options, &worker_channels);
+        if let Err(e) = &result
+        {
+            worker_channels.send_event(WorkerEvent::Error(e.to_string()));
+        }
+        worker_channels.send_event(WorkerEvent::Finished);
+        result
+    })
+}
+
+fn run_video_process(capture: videoio::VideoCapture, 
+                     source_file_path: &std::path::Path,
+                     options: ProcessOptions,
+                     worker_channels: &WorkerThreadAsyncChannels) -> Result<videoio::VideoCapture, VideoProcessorError>
+{
+    let video_info        = VideoInfo::new(&capture)?;
+    let frame_range       = options.get_frame_range(video_info.frame_count);
+    let frame_timing      = options.get_frame_timing(video_info.fps.max(1_f64));
+    let playback_range    = options.get_playback_range(frame_range);
  
-        let frame_sizes   = FrameSizeManager::new(video_info.frame_size, options.flip, options.gui_scale, options.re_scale.unwrap_or(1_f32));
-        let winname       = options.get_video_name(DEFAULT_FILE_NAME);
-        let path_str      = options.get_edit_path_str();
+    let frame_sizes     = options.get_frame_sizes(&video_info);
+    let path_str        = options.get_edit_path_str()?;
+    let post_process    = options.get_post_process(&frame_range, video_info.fps);
+    let mut writer_path = match post_process
+    {
+        Some(_) => ffmpeg::get_intermediate_path(std::path::Path::new(path_str), options.codec.get_extension()),
+        None    => std::path::PathBuf::from(path_str),
+    };
 
-        let mut window = HighGuiWindow::build(winname, highgui::WINDOW_AUTOSIZE)?;
+    let frame_duration = Duration::from_secs_f64(frame_timing.input_frame_duration);
  
 
-        let mut video_writer: Option<VideoWriter> = None;
-        if options.should_process
+    let mut video_writer: Option<VideoWriter> = None;
+    if options.should_process
+    {
+        let params      = options.get_writer_params();
+        let open_writer = |path: &std::path::Path, codec: OutputCodec| open_video_writer(path, codec, frame_timing.output_fps, frame_sizes.get_edit(), &params);
+        // Re-encoded by ffmpeg: written losslessly first, so the quality is only lost once
+        if post_process.as_ref().is_some_and(|post_process| post_process.video_encoding.is_some())
         {
-            let fourcc   = VideoWriter::fourcc('m', 'p', '4', 'v')?;
-            let writer   = VideoWriter::new(path_str, fourcc, video_info.fps, frame_sizes.get_edit(), true).expect("Failed init writer!");
-            video_writer = Some(writer)
-        };
+            let lossless_path = ffmpeg::get_intermediate_path(std::path::Path::new(path_str), OutputCodec::Ffv1.get_extension());
+            video_writer      = open_writer(&lossless_path, OutputCodec::Ffv1)?;
+            match video_writer
+            {
+                Some(_) => writer_path = lossless_path,
+                None    => log::warn!("{} is not available, the video is compressed twice", OutputCodec::Ffv1.get_name()),
+            }
+        }
+        if video_writer.is_none()
+        {
+            // Probing only on failure: it writes a small file
+            let writer   = open_writer(&writer_path, options.codec)?;
+            video_writer = Some(writer.ok_or_else(|| match options.codec.is_supported()
+            {
+                true  => VideoProcessorError::WriterFailed(writer_path.clone()),
+                false => VideoProcessorError::UnsupportedCodec(String::from(options.codec.get_name())),
+            })?);
+        }
+    };
 
+    let mut frame_reader = FrameReader::new(capture, options.direction, frame_range, video_info.frame_size)?;
 
-        let counter = 0;
-        let mut video_renderer: Box<dyn VideoRenderer> = Box::new(PlayMode::new(counter, total_frame_count, frame_sizes));
+    // The renderers count the frames as played, which differs from the frames read when playing backwards
+    let counter = playback_range.start;
+    let mut video_renderer: Box<dyn VideoRenderer> = Box::new(PlayMode::new(counter, playback_range, frame_timing, frame_sizes));
 
-        while worker_channels.is_not_aborted() && window.is_open()
+    let mut progress_tracker = ProgressTracker::new(playback_range.len());
+    let mut is_aborted       = false;
+    while !is_aborted && !video_renderer.has_reached_end()
+    {
+        let frame_start = Instant::now();
+        let mut seek_target = None;
+        for command in worker_channels.receive_commands()
         {
-            if let Some(new_mode) = worker_channels.get_updated_video_mode()
+            match command
             {
-                video_renderer = match new_mode 
+                WorkerCommand::Abort              => is_aborted = true,
+                WorkerCommand::SetVideoMode(mode) =>
                 {
-                    VideoModes::Pause => video_renderer.pause(),
-                    VideoModes::Play  => video_renderer.play(),
+                    video_renderer = match mode 
+                    {
+                        VideoModes::Pause => video_renderer.pause(),
+                        VideoModes::Play  => video_renderer.play(),
+                    }
                 }
+                // Only the last one matters, ex: when scrubbing the timeline
+                WorkerCommand::Seek(target)       => seek_target = Some(target),
+                WorkerCommand::Edit(edit)         => video_renderer.apply_edit(edit),
             }
-            if !video_renderer.read_capture(&mut capture)? || highgui::wait_key(10)? > 0
+        }
+        if is_aborted
+        {
+            break;
+        }
+        let has_seeked = seek_target.is_some();
+        if let Some(target) = seek_target
+        {
+            let frame = target.get_frame(video_info.fps, frame_reader.get_position());
+            video_renderer.seek(&mut frame_reader, frame)?;
+        }
+        if !video_renderer.read_capture(&mut frame_reader)?
+        {
+            break;
+        }
+        // When speeding up, some frames are skipped
+        if !video_renderer.is_dropped()
+        {
+            video_renderer.update_frame()?;
+            // Without preview (ex: from the command line) there is nothing to render
+            if options.preview
             {
-                break;
+                video_renderer.update_preview()?;
+                video_renderer.render(worker_channels)?;
             }
-            video_renderer.update_frame_data(&mut worker_channels);
-            video_renderer.update_frame()?;
-            video_renderer.update_window(&mut window)?;
-            video_renderer.render(&window)?;
             video_renderer.write(&mut video_writer)?;
-            video_renderer.update_frame_counter();
-            video_renderer.send_progression(&mut worker_channels);
+        }
+        video_renderer.update_frame_counter();
+        if let Some(progress) = progress_tracker.update(video_renderer.get_played_frames(), has_seeked)
+        {
+            worker_channels.send_progression(progress);
+            worker_channels.send_position(frame_reader.get_position());
         }
 
-        if let Some(mut writer) = video_writer.take()
+        // When only previewing, the video is played at its own pace. Otherwise we go as fast as possible,
+        // unless paused (possible through the library even when exporting): the same frame is only rendered again
+        if !options.should_process || video_renderer.is_paused()
         {
-            println!("VideoWriter closed at {}%",100_f32 * video_renderer.get_progression());
-            writer.release()?;
+            thread::sleep(frame_duration.saturating_sub(frame_start.elapsed()));
         }
-        Ok(capture)
-    });
+    }
 
-    thread_pool.push(handle);
+    worker_channels.send_progression(progress_tracker.get_progress(video_renderer.get_played_frames()));
+
+    if let Some(mut writer) = video_writer.take()
+    {
+        log::info!("VideoWriter closed at {}%",100_f32 * video_renderer.get_progression());
+        writer.release()?;
+
+        if let Some(post_process) = post_process
+        {
+            match ffmpeg::run_post_process(source_file_path, &writer_path, std::path::Path::new(path_str), &post_process)
+            {
+                Ok(()) => 
+                {
+                    log::info!("ffmpeg post-processing done: {path_str}");
+                    let _ = std::fs::remove_file(&writer_path);
+                }
+                Err(e) => 
+                {
+                    // The lossless intermediate keeps its own container
+                    let output_path = std::path::Path::new(path_str).with_extension(writer_path.extension().unwrap_or_default());
+                    let message     = format!("ffmpeg post-processing failed, {} has no sound and default bitrate: {e}", output_path.display());
+                    worker_channels.send_event(WorkerEvent::Warning(message));
+                    let _ = std::fs::rename(&writer_path, output_path);
+                }
+            }
+        }
+    }
+    Ok(frame_reader.into_capture())
 }
 
  
\ No newline at end of file
@@ -0,0 +1,148 @@
+use opencv::prelude::*;
+use opencv::videoio::VideoWriter;
+use opencv::core::Size;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const PROBE_FILE_NAME: &str = "bubbles_codec_probe";
+const PROBE_FPS: f64        = 25_f64;
+const PROBE_SIZE: Size      = Size { width: 64, height: 64 };
+
+// Jobs & other instances of the app may probe at the same time: each probe writes its own file
+static PROBE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Codec & container of the output file. Which ones can actually be written depends on how OpenCV was built,
+// see "probe_supported_codecs".
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OutputCodec
+{
+    #[default]
+    Mp4v,
+    H264,
+    Mjpg,
+    Xvid,
+    Ffv1,
+}
+
+impl OutputCodec
+{
+    pub const ALL: [OutputCodec; 5] = [OutputCodec::Mp4v, OutputCodec::H264, OutputCodec::Mjpg, OutputCodec::Xvid, OutputCodec::Ffv1];
+
+    pub fn get_fourcc(&self) -> (char, char, char, char)
+    {
+        match self
+        {
+            OutputCodec::Mp4v => ('m', 'p', '4', 'v'),
+            OutputCodec::H264 => ('a', 'v', 'c', '1'),
+            OutputCodec::Mjpg => ('M', 'J', 'P', 'G'),
+            OutputCodec::Xvid => ('X', 'V', 'I', 'D'),
+            OutputCodec::Ffv1 => ('F', 'F', 'V', '1'),
+        }
+    }
+    pub fn get_extension(&self) -> &'static str
+    {
+        match self
+        {
+            OutputCodec::Mp4v | OutputCodec::H264 => "mp4",
+            OutputCodec::Mjpg | OutputCodec::Xvid => "avi",
+            OutputCodec::Ffv1                     => "mkv",
+        }
+    }
+    // Audio codec used by ffmpeg when muxing the sound into this container
+    pub fn get_audio_codec(&self) -> &'static str
+    {
+        match self
+        {
+            OutputCodec::Mjpg | OutputCodec::Xvid => "pcm_s16le",
+            _                                     => "aac",
+        }
+    }
+    // ffmpeg encoder used when re-encoding to a target bitrate
+    pub fn get_ffmpeg_encoder(&self) -> &'static str
+    {
+        match self
+        {
+            OutputCodec::Mp4v | OutputCodec::Xvid => "mpeg4",
+            OutputCodec::H264                     => "libx264",
+            OutputCodec::Mjpg                     => "mjpeg",
+            OutputCodec::Ffv1                     => "ffv1",
+        }
+    }
+    // Rough average of bits per pixel at default quality, used to estimate the output size
+    pub fn get_bits_per_pixel(&self) -> f64
+    {
+        match self
+        {
+            OutputCodec::Mp4v | OutputCodec::Xvid => 0.15,
+            OutputCodec::H264                     => 0.1,
+            OutputCodec::Mjpg                     => 1.5,
+            OutputCodec::Ffv1                     => 8.0,
+        }
+    }
+    // Encoded in 4:2:0, the chroma planes are half the size of the frame
+    pub fn requires_even_size(&self) -> bool
+    {
+        matches!(self, OutputCodec::Mp4v | OutputCodec::H264 | OutputCodec::Xvid)
+    }
+    pub fn is_lossless(&self) -> bool
+    {
+        *self == OutputCodec::Ffv1
+    }
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            OutputCodec::Mp4v => "MPEG-4 (.mp4)",
+            OutputCodec::H264 => "H.264 (.mp4)",
+            OutputCodec::Mjpg => "Motion JPEG (.avi)",
+            OutputCodec::Xvid => "Xvid (.avi)",
+            OutputCodec::Ffv1 => "FFV1 lossless (.mkv)",
+        }
+    }
+    // Short name, used by the command line
+    pub fn get_id(&self) -> &'static str
+    {
+        match self
+        {
+            OutputCodec::Mp4v => "mp4v",
+            OutputCodec::H264 => "h264",
+            OutputCodec::Mjpg => "mjpg",
+            OutputCodec::Xvid => "xvid",
+            OutputCodec::Ffv1 => "ffv1",
+        }
+    }
+    pub fn from_id(id: &str) -> Option<Self>
+    {
+        OutputCodec::ALL.into_iter().find(|codec| codec.get_id().eq_ignore_ascii_case(id))
+    }
+    pub fn get_fourcc_code(&self) -> Result<i32, opencv::Error>
+    {
+        let (c1, c2, c3, c4) = self.get_fourcc();
+        VideoWriter::fourcc(c1, c2, c3, c4)
+    }
+    // Tries to open a small writer in the temp directory
+    pub fn is_supported(&self) -> bool
+    {
+        let probe_id   = PROBE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let probe_path = std::env::temp_dir().join(format!("{PROBE_FILE_NAME}_{}_{probe_id}.{}", std::process::id(), self.get_extension()));
+        let Some(probe_path_str) = probe_path.to_str() else
+        {
+            return false;
+        };
+        let is_supported = self.get_fourcc_code()
+            .and_then(|fourcc| VideoWriter::new(probe_path_str, fourcc, PROBE_FPS, PROBE_SIZE, true))
+            .and_then(|mut writer|
+            {
+                let is_opened = writer.is_opened()?;
+                writer.release()?;
+                Ok(is_opened)
+            })
+            .unwrap_or(false);
+        let _ = std::fs::remove_file(&probe_path);
+        is_supported
+    }
+}
+
+pub fn probe_supported_codecs() -> Vec<OutputCodec>
+{
+    OutputCodec::ALL.into_iter().filter(|codec| codec.is_supported()).collect()
+}
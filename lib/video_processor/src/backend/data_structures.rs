@@ -1,12 +1,31 @@
-use opencv::core::{RotateFlags,Size_};
+use opencv::core::{RotateFlags,Size_,Rect,Scalar,Vec3b};
 use opencv::prelude::*;
-use opencv::{videoio::{self, VideoCapture}, highgui};
+use opencv::{videoio::{self, VideoCapture}, imgproc};
 use std::sync::mpsc::{self};
 use std::thread::{JoinHandle};
-pub type VideoProcThreadPool = Vec<JoinHandle<Result<VideoCapture, opencv::Error>>>;
-use std::sync::mpsc::SendError;
+pub type VideoProcJoinHandle = JoinHandle<Result<VideoCapture, VideoProcessorError>>;
+pub type VideoProcThreadPool = Vec<VideoProcJoinHandle>;
+use std::sync::mpsc::{SendError, TryRecvError};
 use std::ffi::OsString;
+use std::time::{Duration, Instant};
 use crate::backend::helper_function::*;
+use crate::backend::codec::OutputCodec;
+use crate::backend::ffmpeg::{AudioSegment, PostProcess, VideoEncoding};
+use crate::backend::error::VideoProcessorError;
+
+const AUDIO_BITS_PER_SEC: f64 = 128_000_f64; // ffmpeg's default for aac
+const MIN_SLOW_MOTION_FPS: f64 = 24_f64;
+pub const MIN_SPEED: f64 = 0.25;
+pub const MAX_SPEED: f64 = 16_f64;
+pub const MAX_COLOR_ADJUST: f64 = 100_f64; // brightness, contrast, saturation & temperature go from -100 to 100
+pub const MAX_HUE_SHIFT: f64    = 180_f64; // in degrees, both ways
+pub const MIN_GAMMA: f64        = 0.2;
+pub const MAX_GAMMA: f64        = 5_f64;
+pub const MAX_ASPECT_VALUE: u32 = 100;     // each side of the padding aspect ratio goes from 1 to this
+const TEMPERATURE_GAIN: f64     = 0.3;     // red & blue gains at the warmest or coolest temperature: 1 +/- this
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+const FPS_SMOOTHING: f64          = 0.25; // weight of the last interval in the processing fps
+const MIN_ETA_FPS: f64            = 0.1;  // slower than that (ex: paused), the remaining time is unknown
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum VideoModes
@@ -15,6 +34,26 @@ pub enum VideoModes
     Pause,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SeekTarget
+{
+    Frame(usize),
+    Time(f64),    // in seconds from the start of the source video
+    Step(isize),  // relative to the current frame, ex: -1 for the previous frame
+}
+impl SeekTarget
+{
+    pub fn get_frame(&self, fps: f64, current_frame: usize) -> usize
+    {
+        match self
+        {
+            SeekTarget::Frame(frame) => *frame,
+            SeekTarget::Time(secs)   => (secs.max(0_f64) * fps).round() as usize,
+            SeekTarget::Step(frames) => current_frame.saturating_add_signed(*frames),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct VideoInfo
 {
@@ -28,14 +67,15 @@ use super::helper_function::decode_fourcc;
 impl VideoInfo
 {
     pub fn new(
-        capture: &VideoCapture) -> Result<Self, opencv::Error>
+        capture: &VideoCapture) -> Result<Self, VideoProcessorError>
     {
         let frame_count  = capture.get(videoio::CAP_PROP_FRAME_COUNT)? as usize;
         let height       = capture.get(videoio::CAP_PROP_FRAME_HEIGHT)? as i32;
         let width        = capture.get(videoio::CAP_PROP_FRAME_WIDTH)? as i32;
         let fps          = capture.get(videoio::CAP_PROP_FPS)? as f64;
         let fourcc_codec = capture.get(videoio::CAP_PROP_FOURCC)? as u32;
-        let fourcc_codec = decode_fourcc(fourcc_codec).expect("FourCC cannot be negative");
+        let fourcc_codec = decode_fourcc(fourcc_codec)
+            .ok_or_else(|| VideoProcessorError::UnsupportedCodec(format!("unknown FourCC {fourcc_codec:#010x}")))?;
         let frame_size = opencv::core::Size 
         {
             width,
@@ -50,7 +90,7 @@ impl VideoInfo
         })
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 
 pub struct ProcessOptions
 {
@@ -59,7 +99,21 @@ pub struct ProcessOptions
     pub flip: Option<RotateFlags>,
     pub should_process: bool,
     pub preview: bool,
-    pub re_scale: Option<f32>,
+    pub resize: Resize,
+    pub padding: Option<Padding>,
+    pub start_frame: Option<usize>,
+    pub end_frame: Option<usize>,
+    pub keep_audio: bool,
+    pub codec: OutputCodec,
+    pub quality: Option<f64>,
+    pub bitrate_kbps: Option<u32>,
+    pub crop: Option<Rect>,
+    pub mirror: Mirror,
+    pub free_rotation: FreeRotation,
+    pub color_adjust: ColorAdjust,
+    pub speed: f64,
+    pub blend_frames: bool,
+    pub direction: PlaybackDirection,
 }
 
 impl Default for ProcessOptions
@@ -72,123 +126,923 @@ impl Default for ProcessOptions
             flip: None,
             should_process: false,
             preview: true,
-            re_scale: None,
+            resize: Resize::default(),
+            padding: None,
+            start_frame: None,
+            end_frame: None,
+            keep_audio: false,
+            codec: OutputCodec::default(),
+            quality: None,
+            bitrate_kbps: None,
+            crop: None,
+            mirror: Mirror::default(),
+            free_rotation: FreeRotation::default(),
+            color_adjust: ColorAdjust::default(),
+            speed: 1_f64,
+            blend_frames: false,
+            direction: PlaybackDirection::default(),
         }
     }    
 }
 impl ProcessOptions
 {
-    pub fn get_edit_path_str(&self) -> &str
+    pub fn get_edit_path_str(&self) -> Result<&str, VideoProcessorError>
     {
-        let mut path_str = self.edit_file_path.to_str().expect("Not empty");    
+        let mut path_str = self.edit_file_path.to_str()
+            .ok_or_else(|| VideoProcessorError::NonUtf8Path(self.edit_file_path.clone()))?;
         if path_str.ends_with("\"") && path_str.starts_with("\"")
         {
             path_str = path_str.trim_matches('\"');
         }
-        path_str
+        Ok(path_str)
     }    
     pub fn get_video_name(&self, default: &str) -> String
     {
         let default   = OsString::from(default);
         let file_name = self.edit_file_path.file_name()
             .unwrap_or(&default)
-            .to_str()
-            .expect("Failed converting OsString to &str");
+            .to_string_lossy();
         String::from(file_name)
     }
+    pub fn get_writer_params(&self) -> opencv::core::Vector<i32>
+    {
+        let mut params = opencv::core::Vector::<i32>::new();
+        params.push(videoio::VIDEOWRITER_PROP_IS_COLOR);
+        params.push(1);
+        if let Some(quality) = self.quality
+        {
+            params.push(videoio::VIDEOWRITER_PROP_QUALITY);
+            params.push(quality.clamp(0_f64, 100_f64) as i32);
+        }
+        params
+    }
+    // None if nothing has to be done by ffmpeg after the VideoWriter is closed
+    pub fn get_post_process(&self, frame_range: &FrameRange, fps: f64) -> Option<PostProcess>
+    {
+        if !self.should_process || (!self.keep_audio && self.get_bitrate_kbps().is_none())
+        {
+            return None;
+        }
+        let audio = self.keep_audio.then(|| AudioSegment
+        {
+            start_secs:    frame_range.start as f64 / fps,
+            duration_secs: frame_range.len() as f64 / fps,
+            tempo:         self.get_speed(),
+            direction:     self.direction,
+        });
+        let video_encoding = self.get_bitrate_kbps().map(|bitrate_kbps| VideoEncoding
+        {
+            encoder: self.codec.get_ffmpeg_encoder(),
+            bitrate_kbps,
+        });
+        Some(PostProcess { audio, audio_codec: self.codec.get_audio_codec(), video_encoding })
+    }
+    // A lossless codec has no bitrate
+    pub fn get_bitrate_kbps(&self) -> Option<u32>
+    {
+        self.bitrate_kbps.filter(|_| !self.codec.is_lossless())
+    }
+    // Rough estimation in bytes, based on the target bitrate if any, otherwise on the codec & quality
+    pub fn estimate_output_size(&self, video_info: &VideoInfo) -> u64
+    {
+        let frame_range   = self.get_frame_range(video_info.frame_count);
+        let frame_timing  = self.get_frame_timing(video_info.fps.max(1_f64));
+        let duration_secs = self.get_playback_range(frame_range).len() as f64 / video_info.fps.max(1_f64) / self.get_speed();
+        let frame_size    = self.get_frame_sizes(video_info).get_edit();
+
+        let video_bits_per_sec = match self.get_bitrate_kbps()
+        {
+            Some(bitrate_kbps) => 1000_f64 * bitrate_kbps as f64,
+            None               =>
+            {
+                let quality_factor = match self.codec.is_lossless()
+                {
+                    true  => 1_f64,
+                    false => self.quality.map_or(1_f64, |quality| 0.2 + 1.6 * quality.clamp(0_f64, 100_f64) / 100_f64),
+                };
+                let pixels_per_sec = (frame_size.width * frame_size.height) as f64 * frame_timing.output_fps;
+                pixels_per_sec * self.codec.get_bits_per_pixel() * quality_factor
+            }
+        };
+        let audio_bits_per_sec = if self.keep_audio { AUDIO_BITS_PER_SEC } else { 0_f64 };
+        ((video_bits_per_sec + audio_bits_per_sec) * duration_secs / 8_f64) as u64
+    }
+    pub fn get_frame_sizes(&self, video_info: &VideoInfo) -> FrameSizeManager
+    {
+        let mut frame_sizes = FrameSizeManager::new(video_info.frame_size, self.crop, self.flip, self.gui_scale, self.resize);
+        frame_sizes.set_even_size(self.codec.requires_even_size());
+        frame_sizes.mirror(self.mirror);
+        frame_sizes.rotate_freely(self.free_rotation);
+        frame_sizes.pad(self.padding);
+        frame_sizes.adjust_colors(self.color_adjust);
+        frame_sizes
+    }
+    pub fn get_speed(&self) -> f64
+    {
+        self.speed.clamp(MIN_SPEED, MAX_SPEED)
+    }
+    pub fn get_frame_timing(&self, fps: f64) -> FrameTiming
+    {
+        FrameTiming::new(fps, self.get_speed(), self.blend_frames)
+    }
+    pub fn get_frame_range(&self, frame_count: usize) -> FrameRange
+    {
+        FrameRange::new(self.start_frame.unwrap_or(0), self.end_frame.unwrap_or(frame_count).min(frame_count))
+    }
+    // The frames as played: the boomerang shows the range twice, sharing the last frame.
+    // Starts at "frame_range.start" like the frame counter of the renderers.
+    pub fn get_playback_range(&self, frame_range: FrameRange) -> FrameRange
+    {
+        let playback_len = match self.direction
+        {
+            PlaybackDirection::Boomerang => (2 * frame_range.len()).saturating_sub(1),
+            _                            => frame_range.len(),
+        };
+        FrameRange::new(frame_range.start, frame_range.start + playback_len)
+    }
 }
 
-#[derive(Debug)]
-pub struct MainThreadAsyncChannels
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum PlaybackDirection
 {
-    pub rx_progression_from_thread: mpsc::Receiver<f32>,
-    pub tx_abort_signal_to_thread: mpsc::Sender<bool>,
-    pub tx_video_mode: mpsc::Sender<VideoModes>,
-    pub tx_flip_update: mpsc::Sender<Option<RotateFlags>>,
-    pub tx_rescale_update: mpsc::Sender<f32>,
-    // pub rx_open_status: mpsc::Receiver<bool>,        // Could be useful, maybe not?
-    pub tx_highgui_size_update: mpsc::Sender<f32>,
+    #[default]
+    Forward,
+    Reverse,
+    Boomerang, // forward then backward
 }
-impl MainThreadAsyncChannels
+impl PlaybackDirection
 {
-    pub fn get_last_progression(&self) -> Option<f32>
+    pub const ALL: [PlaybackDirection; 3] = [PlaybackDirection::Forward, PlaybackDirection::Reverse, PlaybackDirection::Boomerang];
+
+    pub fn get_name(&self) -> &'static str
     {
-        self.rx_progression_from_thread.try_iter().last()
+        match self
+        {
+            PlaybackDirection::Forward   => "Forward",
+            PlaybackDirection::Reverse   => "Reverse",
+            PlaybackDirection::Boomerang => "Boomerang",
+        }
     }
-    pub fn send_abort_signal(&self) -> Result<(), SendError<bool>>
+    // Short name, used by the command line
+    pub fn get_id(&self) -> &'static str
     {
-        self.tx_abort_signal_to_thread.send(true)?;
-        Ok(())
+        match self
+        {
+            PlaybackDirection::Forward   => "forward",
+            PlaybackDirection::Reverse   => "reverse",
+            PlaybackDirection::Boomerang => "boomerang",
+        }
     }
-    // pub fn get_open_status(&self) -> Option<bool>  // Could be useful, maybe not?
-    // {
-    //     self.rx_open_status.try_iter().last()
-    // }
-    pub fn send_new_video_mode(&self, video_mode: VideoModes) -> Result<(), SendError<VideoModes>>
+    pub fn from_id(id: &str) -> Option<Self>
     {
-        self.tx_video_mode.send(video_mode)?;
-        Ok(())
+        PlaybackDirection::ALL.into_iter().find(|direction| direction.get_id().eq_ignore_ascii_case(id))
     }
-    pub fn send_new_gui_size(&self, new_gui_size: f32) -> Result<(), SendError<f32>>
+}
+
+// Mirroring is applied after the rotation, i.e. "horizontal" is relative to the edited video.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Mirror
+{
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+impl Mirror
+{
+    pub fn is_mirrored(&self) -> bool
     {
-        self.tx_highgui_size_update.send(new_gui_size)?;
-        Ok(())
+        self.horizontal || self.vertical
+    }
+    // flipCode of opencv::core::flip: 1 around the y-axis, 0 around the x-axis, -1 around both
+    pub fn get_flip_code(&self) -> Option<i32>
+    {
+        match (self.horizontal, self.vertical)
+        {
+            (true,  false) => Some(1),
+            (false, true)  => Some(0),
+            (true,  true)  => Some(-1),
+            (false, false) => None,
+        }
+    }
+}
+
+// What to do with the corners of a frame rotated by a free angle
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum RotationFill
+{
+    #[default]
+    Expand, // the canvas grows to contain the whole rotated frame
+    Crop,   // largest rectangle (same aspect ratio) inside the rotated frame, no border
+    Fill,   // same size as before the rotation, the corners are cut
+}
+
+impl RotationFill
+{
+    pub const ALL: [RotationFill; 3] = [RotationFill::Expand, RotationFill::Crop, RotationFill::Fill];
+
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            RotationFill::Expand => "Expand canvas",
+            RotationFill::Crop   => "Crop to fit",
+            RotationFill::Fill   => "Fill corners",
+        }
+    }
+    // Short name, used by the command line
+    pub fn get_id(&self) -> &'static str
+    {
+        match self
+        {
+            RotationFill::Expand => "expand",
+            RotationFill::Crop   => "crop",
+            RotationFill::Fill   => "fill",
+        }
+    }
+    pub fn from_id(id: &str) -> Option<Self>
+    {
+        RotationFill::ALL.into_iter().find(|fill| fill.get_id().eq_ignore_ascii_case(id))
+    }
+}
+
+// Clockwise rotation by any angle, applied after the 90° rotations
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct FreeRotation
+{
+    pub angle: f64, // in degrees
+    pub fill: RotationFill,
+    pub fill_color: [u8; 3], // RGB, color of the borders for "Expand" & "Fill"
+}
+impl FreeRotation
+{
+    pub fn is_rotated(&self) -> bool
+    {
+        self.angle != 0_f64
+    }
+    pub fn get_border_color(&self) -> Scalar
+    {
+        let [red, green, blue] = self.fill_color;
+        Scalar::new(blue as f64, green as f64, red as f64, 0_f64)
+    }
+}
+
+// Exposure & colors of the transformed frame. The defaults change nothing
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ColorAdjust
+{
+    pub brightness: f64,  // -100 to 100
+    pub contrast: f64,    // -100 to 100
+    pub saturation: f64,  // -100 (grey) to 100
+    pub gamma: f64,       // above 1: brighter shadows, below: darker
+    pub hue_shift: f64,   // in degrees
+    pub temperature: f64, // -100 (bluer) to 100 (warmer), the white balance
+}
+
+impl Default for ColorAdjust
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            brightness: 0_f64,
+            contrast: 0_f64,
+            saturation: 0_f64,
+            gamma: 1_f64,
+            hue_shift: 0_f64,
+            temperature: 0_f64,
+        }
+    }
+}
+
+impl ColorAdjust
+{
+    // Every value within its range
+    pub fn get_clamped(&self) -> Self
+    {
+        let clamp = |value: f64| value.clamp(-MAX_COLOR_ADJUST, MAX_COLOR_ADJUST);
+        Self
+        {
+            brightness: clamp(self.brightness),
+            contrast: clamp(self.contrast),
+            saturation: clamp(self.saturation),
+            gamma: self.gamma.clamp(MIN_GAMMA, MAX_GAMMA),
+            hue_shift: self.hue_shift.clamp(-MAX_HUE_SHIFT, MAX_HUE_SHIFT),
+            temperature: clamp(self.temperature),
+        }
+    }
+    pub fn is_adjusted(&self) -> bool
+    {
+        self.has_tone_change() || self.has_hsv_change()
+    }
+    // Done channel by channel on the BGR frame
+    pub fn has_tone_change(&self) -> bool
+    {
+        self.brightness != 0_f64 || self.contrast != 0_f64 || self.gamma != 1_f64 || self.temperature != 0_f64
+    }
+    // Done on the HSV frame
+    pub fn has_hsv_change(&self) -> bool
+    {
+        self.saturation != 0_f64 || self.hue_shift != 0_f64
+    }
+    // White balance gain -> brightness -> contrast -> gamma
+    fn get_tone(&self, value: u8, gain: f64) -> u8
+    {
+        let value    = value as f64 / 255_f64 * gain + self.brightness / MAX_COLOR_ADJUST / 2_f64;
+        let contrast = 1_f64 + self.contrast / MAX_COLOR_ADJUST;
+        let value    = ((value - 0.5) * contrast + 0.5).clamp(0_f64, 1_f64);
+        (value.powf(1_f64 / self.gamma.max(MIN_GAMMA)) * 255_f64).round() as u8
+    }
+    // For opencv::core::lut on a BGR frame
+    pub fn get_tone_table(&self) -> Result<Mat, opencv::Error>
+    {
+        let warmth    = self.temperature / MAX_COLOR_ADJUST * TEMPERATURE_GAIN;
+        let mut table = Mat::new_rows_cols_with_default(1, 256, opencv::core::CV_8UC3, Scalar::all(0_f64))?;
+        for value in 0..=u8::MAX
+        {
+            *table.at_2d_mut::<Vec3b>(0, value as i32)? = Vec3b::new(self.get_tone(value, 1_f64 - warmth), self.get_tone(value, 1_f64), self.get_tone(value, 1_f64 + warmth));
+        }
+        Ok(table)
+    }
+    // For opencv::core::lut on a HSV frame, whose hue goes from 0 to 179 (in 2° steps)
+    pub fn get_hsv_table(&self) -> Result<Mat, opencv::Error>
+    {
+        let hue_shift  = (self.hue_shift / 2_f64).round() as i32;
+        let saturation = 1_f64 + self.saturation / MAX_COLOR_ADJUST;
+        let mut table  = Mat::new_rows_cols_with_default(1, 256, opencv::core::CV_8UC3, Scalar::all(0_f64))?;
+        for value in 0..=u8::MAX
+        {
+            let hue = match value < 180
+            {
+                true  => (value as i32 + hue_shift).rem_euclid(180) as u8,
+                false => value,
+            };
+            let saturated = (value as f64 * saturation).round().clamp(0_f64, 255_f64) as u8;
+            *table.at_2d_mut::<Vec3b>(0, value as i32)? = Vec3b::new(hue, saturated, value);
+        }
+        Ok(table)
+    }
+}
+
+// Pixel interpolation of the resizes (edited frame & preview)
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Interpolation
+{
+    #[default]
+    Auto,    // area when shrinking, linear when enlarging
+    Linear,
+    Area,    // sharpest & without moiré when shrinking
+    Cubic,
+    Lanczos,
+}
+
+impl Interpolation
+{
+    pub const ALL: [Interpolation; 5] = [Interpolation::Auto, Interpolation::Linear, Interpolation::Area, Interpolation::Cubic, Interpolation::Lanczos];
+
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            Interpolation::Auto    => "Auto",
+            Interpolation::Linear  => "Linear",
+            Interpolation::Area    => "Area",
+            Interpolation::Cubic   => "Cubic",
+            Interpolation::Lanczos => "Lanczos",
+        }
+    }
+    // Short name, used by the command line
+    pub fn get_id(&self) -> &'static str
+    {
+        match self
+        {
+            Interpolation::Auto    => "auto",
+            Interpolation::Linear  => "linear",
+            Interpolation::Area    => "area",
+            Interpolation::Cubic   => "cubic",
+            Interpolation::Lanczos => "lanczos",
+        }
+    }
+    pub fn from_id(id: &str) -> Option<Self>
+    {
+        Interpolation::ALL.into_iter().find(|interpolation| interpolation.get_id().eq_ignore_ascii_case(id))
+    }
+    // Flag of opencv::imgproc::resize, to resize from "src_size" to "dst_size"
+    pub fn get_flag(&self, src_size: Size_<i32>, dst_size: Size_<i32>) -> i32
+    {
+        let is_shrinking = dst_size.width * dst_size.height < src_size.width * src_size.height;
+        match self
+        {
+            Interpolation::Auto if is_shrinking         => imgproc::INTER_AREA,
+            Interpolation::Auto | Interpolation::Linear => imgproc::INTER_LINEAR,
+            Interpolation::Area                         => imgproc::INTER_AREA,
+            Interpolation::Cubic                        => imgproc::INTER_CUBIC,
+            Interpolation::Lanczos                      => imgproc::INTER_LANCZOS4,
+        }
+    }
+}
+
+// Size of the edited frame, from the frame cropped & rotated. Except "Scale", the aspect ratio is kept
+// and the frame can be enlarged to reach the target.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ResizeMode
+{
+    Scale(f32),    // 1 = unchanged
+    Width(i32),    // the height follows
+    Height(i32),   // the width follows
+    Fit(i32, i32), // largest size within width x height
+}
+
+impl Default for ResizeMode
+{
+    fn default() -> Self
+    {
+        ResizeMode::Scale(1_f32)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Resize
+{
+    pub mode: ResizeMode,
+    pub interpolation: Interpolation,
+}
+
+impl Resize
+{
+    pub fn is_resized(&self) -> bool
+    {
+        self.mode != ResizeMode::Scale(1_f32)
+    }
+    // "even_size": the width & height are rounded down to even numbers (ex: H.264 in 4:2:0)
+    pub fn get_size(&self, size: Size_<i32>, even_size: bool) -> Size_<i32>
+    {
+        let (width, height) = (size.width as f64, size.height as f64);
+        let get_height      = |new_width: i32| (height * new_width as f64 / width).round() as i32;
+        let get_width       = |new_height: i32| (width * new_height as f64 / height).round() as i32;
+        let mut new_size    = match self.mode
+        {
+            ResizeMode::Scale(scale)               => size.get_resized(scale),
+            ResizeMode::Width(new_width)           => Size_::new(new_width, get_height(new_width)),
+            ResizeMode::Height(new_height)         => Size_::new(get_width(new_height), new_height),
+            ResizeMode::Fit(max_width, max_height) => match get_height(max_width) <= max_height
+            {
+                true  => Size_::new(max_width, get_height(max_width)),
+                false => Size_::new(get_width(max_height), max_height),
+            },
+        };
+        if even_size
+        {
+            new_size.width  -= new_size.width % 2;
+            new_size.height -= new_size.height % 2;
+        }
+        let min_size = if even_size { 2 } else { 1 };
+        Size_::new(new_size.width.max(min_size), new_size.height.max(min_size))
+    }
+}
+
+// Background of the padding
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum PadFill
+{
+    #[default]
+    Color,
+    Blur,  // the frame itself, enlarged & blurred
+}
+
+impl PadFill
+{
+    pub const ALL: [PadFill; 2] = [PadFill::Color, PadFill::Blur];
+
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            PadFill::Color => "Solid color",
+            PadFill::Blur  => "Blurred video",
+        }
+    }
+    // Short name, used by the command line
+    pub fn get_id(&self) -> &'static str
+    {
+        match self
+        {
+            PadFill::Color => "color",
+            PadFill::Blur  => "blur",
+        }
+    }
+    pub fn from_id(id: &str) -> Option<Self>
+    {
+        PadFill::ALL.into_iter().find(|fill| fill.get_id().eq_ignore_ascii_case(id))
+    }
+}
+
+// Letterbox or pillarbox: the resized frame is centered on a canvas of the "aspect" ratio, nothing is cut
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Padding
+{
+    pub aspect: (u32, u32), // ex: (9, 16) for a vertical video
+    pub fill: PadFill,
+    pub color: [u8; 3],     // RGB, for PadFill::Color
+}
+
+impl Default for Padding
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            aspect: (9, 16),
+            fill: PadFill::default(),
+            color: [0, 0, 0],
+        }
+    }
+}
+
+impl Padding
+{
+    pub fn get_color(&self) -> Scalar
+    {
+        let [red, green, blue] = self.color;
+        Scalar::new(blue as f64, green as f64, red as f64, 0_f64)
+    }
+    // Each side from 1 to MAX_ASPECT_VALUE
+    pub fn is_aspect_valid(aspect: (u32, u32)) -> bool
+    {
+        (1..=MAX_ASPECT_VALUE).contains(&aspect.0) && (1..=MAX_ASPECT_VALUE).contains(&aspect.1)
+    }
+    // Smallest canvas of the aspect ratio containing "size". "even_size": rounded up to even numbers
+    pub fn get_size(&self, size: Size_<i32>, even_size: bool) -> Size_<i32>
+    {
+        let (aspect_width, aspect_height) = self.aspect;
+        if aspect_width == 0 || aspect_height == 0
+        {
+            return size;
+        }
+        // Clamped so that rounding up to an even size cannot overflow
+        let to_size      = |value: f64| value.round().min((i32::MAX - 1) as f64) as i32;
+        let ratio        = aspect_width as f64 / aspect_height as f64;
+        let mut new_size = match (size.width as f64 / size.height as f64) < ratio
+        {
+            true  => Size_::new(to_size(size.height as f64 * ratio), size.height), // pillarbox
+            false => Size_::new(size.width, to_size(size.width as f64 / ratio)),   // letterbox
+        };
+        new_size.width  = new_size.width.max(size.width);
+        new_size.height = new_size.height.max(size.height);
+        if even_size
+        {
+            new_size.width  += new_size.width % 2;
+            new_size.height += new_size.height % 2;
+        }
+        new_size
+    }
+}
+
+// The in/out points of the edit: "start" is the first frame read, "end" is excluded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameRange
+{
+    pub start: usize,
+    pub end: usize,
+}
+impl FrameRange
+{
+    pub fn new(start: usize, end: usize) -> Self
+    {
+        Self { start: start.min(end), end }
+    }
+    pub fn len(&self) -> usize
+    {
+        self.end - self.start
     }
-    pub fn send_new_rescale(&self, new_rescale: f32) -> Result<(), SendError<f32>>
+    pub fn is_empty(&self) -> bool
     {
-        self.tx_rescale_update.send(new_rescale)?;
+        self.len() == 0
+    }
+    pub fn is_finished(&self, counter: usize) -> bool
+    {
+        counter >= self.end
+    }
+    pub fn get_progression(&self, counter: usize) -> f32
+    {
+        if self.is_empty()
+        {
+            return 1_f32;
+        }
+        counter.saturating_sub(self.start) as f32 / self.len() as f32
+    }
+}
+
+// Maps the input frames to the output frames when the speed is changed:
+// frames are dropped to speed up, and duplicated to slow down.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTiming
+{
+    pub output_fps: f64,
+    pub input_frame_duration: f64, // in seconds of output, used to pace the preview
+    pub blend: bool,               // average the dropped frames into the written ones
+    frames_per_input: f64,         // < 1 drops frames, > 1 duplicates them
+}
+impl FrameTiming
+{
+    pub fn new(fps: f64, speed: f64, blend: bool) -> Self
+    {
+        // High frame rate sources are slowed down by lowering the fps rather than duplicating frames
+        let output_fps = (fps * speed).clamp(MIN_SLOW_MOTION_FPS.min(fps), fps);
+        Self
+        {
+            output_fps,
+            input_frame_duration: 1_f64 / (fps * speed),
+            blend: blend && speed > 1_f64,
+            frames_per_input: output_fps / (fps * speed),
+        }
+    }
+    // How many times the input frame "counter" is written, 0 if it is dropped
+    pub fn get_output_count(&self, counter: usize) -> usize
+    {
+        let first_output = (counter as f64 * self.frames_per_input).ceil() as usize;
+        let next_output  = ((counter + 1) as f64 * self.frames_per_input).ceil() as usize;
+        next_output - first_output
+    }
+}
+
+// Sum of the frames dropped since the last written frame (timelapse "motion blur")
+#[derive(Debug, Default)]
+pub struct FrameBlender
+{
+    sum: Mat,
+    count: usize,
+}
+impl FrameBlender
+{
+    pub fn add(&mut self, frame: &Mat) -> Result<(), opencv::Error>
+    {
+        match self.count
+        {
+            0 => frame.convert_to(&mut self.sum, opencv::core::CV_32F, 1_f64, 0_f64)?,
+            _ => imgproc::accumulate(frame, &mut self.sum, &opencv::core::no_array())?,
+        }
+        self.count += 1;
         Ok(())
     }
-    pub fn send_new_flip(&self, new_flip: Option<RotateFlags>) -> Result<(), SendError<Option<RotateFlags>>>
+    // Writes the average of the added frames into "frame" and starts a new sum
+    pub fn take_average(&mut self, frame: &mut Mat) -> Result<(), opencv::Error>
     {
-        self.tx_flip_update.send(new_flip)?;
+        if self.count > 0
+        {
+            self.sum.convert_to(frame, opencv::core::CV_8U, 1_f64 / self.count as f64, 0_f64)?;
+            self.count = 0;
+        }
         Ok(())
     }
 }
 
+// Everything the main thread can ask to the worker thread
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WorkerCommand
+{
+    Abort,
+    SetVideoMode(VideoModes),
+    Seek(SeekTarget),
+    Edit(FrameEdit),
+}
+
+// Live changes of the edited frame, applied by the FrameSizeManager
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FrameEdit
+{
+    Flip(Option<RotateFlags>),
+    Resize(Resize),
+    GuiScale(f32),
+    Crop(Option<Rect>),
+    Mirror(Mirror),
+    FreeRotation(FreeRotation),
+    Padding(Option<Padding>),
+    ColorAdjust(ColorAdjust),
+}
+impl FrameEdit
+{
+    // false if only the preview changes, the transformed frame stays the same (it is resized & padded afterwards)
+    pub fn is_transform(&self) -> bool
+    {
+        !matches!(self, FrameEdit::Resize(_) | FrameEdit::GuiScale(_) | FrameEdit::Padding(_))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Progress
+{
+    pub frame: usize,                // frames played so far
+    pub total_frames: usize,
+    pub fps: f64,                    // frames played per second, smoothed
+    pub elapsed: Duration,
+    pub remaining: Option<Duration>, // unknown until frames are played, or while paused
+}
+impl Progress
+{
+    pub fn get_fraction(&self) -> f32
+    {
+        if self.total_frames == 0
+        {
+            return 1_f32;
+        }
+        (self.frame as f32 / self.total_frames as f32).min(1_f32)
+    }
+}
+
+// Measures the processing speed on the worker side, and limits how often the progress is sent
+pub struct ProgressTracker
+{
+    total_frames: usize,
+    start: Instant,
+    interval_start: Instant,
+    interval_start_frame: usize,
+    fps: f64,
+}
+impl ProgressTracker
+{
+    pub fn new(total_frames: usize) -> Self
+    {
+        let now = Instant::now();
+        Self
+        {
+            total_frames,
+            start: now,
+            interval_start: now,
+            interval_start_frame: 0,
+            fps: 0_f64,
+        }
+    }
+    // Returns the progress to send, at most every PROGRESS_INTERVAL unless "has_seeked":
+    // the frames skipped by a seek do not count in the processing speed, but the GUI should see the new position
+    pub fn update(&mut self, frame: usize, has_seeked: bool) -> Option<Progress>
+    {
+        let now      = Instant::now();
+        let interval = now - self.interval_start;
+        if !has_seeked && interval < PROGRESS_INTERVAL
+        {
+            return None;
+        }
+        if !has_seeked
+        {
+            let interval_fps = frame.saturating_sub(self.interval_start_frame) as f64 / interval.as_secs_f64();
+            self.fps         = match self.fps > 0_f64
+            {
+                true  => FPS_SMOOTHING * interval_fps + (1_f64 - FPS_SMOOTHING) * self.fps,
+                false => interval_fps,
+            };
+        }
+        self.interval_start       = now;
+        self.interval_start_frame = frame;
+        Some(self.get_progress(frame))
+    }
+    pub fn get_progress(&self, frame: usize) -> Progress
+    {
+        let remaining_frames = self.total_frames.saturating_sub(frame);
+        Progress
+        {
+            frame,
+            total_frames: self.total_frames,
+            fps: self.fps,
+            elapsed: self.start.elapsed(),
+            remaining: (self.fps >= MIN_ETA_FPS).then(|| Duration::from_secs_f64(remaining_frames as f64 / self.fps)),
+        }
+    }
+}
+
+// Everything the worker thread reports to the main thread (the preview frames have their own channel)
+#[derive(Debug, PartialEq, Clone)]
+pub enum WorkerEvent
+{
+    Progress(Progress),
+    Position(usize),    // index in the source video of the last frame read
+    Warning(String),
+    Error(String),
+    Finished,
+}
+
+// The preview frames are heavy, they go through a bounded channel so that a lagging GUI drops them
+pub fn create_async_channels(preview_frame_buffer: usize) -> (MainThreadAsyncChannels, WorkerThreadAsyncChannels)
+{
+    let (tx_command,       rx_command)       = mpsc::channel();
+    let (tx_event,         rx_event)         = mpsc::channel();
+    let (tx_preview_frame, rx_preview_frame) = mpsc::sync_channel(preview_frame_buffer);
+
+    let main_channels = MainThreadAsyncChannels
+    {
+        tx_command,
+        rx_event,
+        rx_preview_frame,
+        last_progression: None,
+        last_position: None,
+        messages: Vec::new(),
+    };
+    let worker_channels = WorkerThreadAsyncChannels
+    {
+        rx_command,
+        tx_event,
+        tx_preview_frame,
+    };
+    (main_channels, worker_channels)
+}
+
 #[derive(Debug)]
+pub struct MainThreadAsyncChannels
+{
+    pub tx_command: mpsc::Sender<WorkerCommand>,
+    pub rx_event: mpsc::Receiver<WorkerEvent>,
+    pub rx_preview_frame: mpsc::Receiver<PreviewFrame>,
+    // The events are received all at once, and kept until asked for
+    last_progression: Option<Progress>,
+    last_position: Option<usize>,
+    messages: Vec<WorkerEvent>,     // warnings, errors & end of the process
+}
+impl MainThreadAsyncChannels
+{
+    pub fn send_command(&self, command: WorkerCommand) -> Result<(), SendError<WorkerCommand>>
+    {
+        self.tx_command.send(command)?;
+        Ok(())
+    }
+    fn receive_events(&mut self)
+    {
+        for event in self.rx_event.try_iter()
+        {
+            match event
+            {
+                WorkerEvent::Progress(progression) => self.last_progression = Some(progression),
+                WorkerEvent::Position(position)    => self.last_position    = Some(position),
+                message                            => self.messages.push(message),
+            }
+        }
+    }
+    pub fn get_last_progression(&mut self) -> Option<Progress>
+    {
+        self.receive_events();
+        self.last_progression.take()
+    }
+    pub fn get_last_position(&mut self) -> Option<usize>
+    {
+        self.receive_events();
+        self.last_position.take()
+    }
+    pub fn take_messages(&mut self) -> Vec<WorkerEvent>
+    {
+        self.receive_events();
+        std::mem::take(&mut self.messages)
+    }
+    pub fn get_last_preview_frame(&self) -> Option<PreviewFrame>
+    {
+        self.rx_preview_frame.try_iter().last()
+    }
+}
 
+#[derive(Debug)]
 pub struct WorkerThreadAsyncChannels
 {
-    pub tx_progression_to_main: mpsc::Sender<f32>,
-    pub rx_abort_signal_from_main: mpsc::Receiver<bool>,
-    pub rx_video_mode: mpsc::Receiver<VideoModes>,
-    pub rx_flip_update: mpsc::Receiver<Option<RotateFlags>>,
-    pub rx_rescale_update: mpsc::Receiver<f32>,
-    // pub tx_open_status: mpsc::Sender<bool>,              // Could be useful, maybe not?
-    pub rx_highgui_size_update: mpsc::Receiver<f32>,
+    pub rx_command: mpsc::Receiver<WorkerCommand>,
+    pub tx_event: mpsc::Sender<WorkerEvent>,
+    pub tx_preview_frame: mpsc::SyncSender<PreviewFrame>,
 }
 
 impl WorkerThreadAsyncChannels
 {
-    pub fn get_updated_video_mode(&mut self) -> Option<VideoModes>
-    {
-        self.rx_video_mode.try_iter().last()
-    }   
-    pub fn get_last_size_update(&mut self) -> Option<f32>
+    // All the commands sent since the last call, in order.
+    // If the main thread dropped its channels, nobody is waiting for the result anymore: abort
+    pub fn receive_commands(&self) -> Vec<WorkerCommand>
     {
-        self.rx_highgui_size_update.try_iter().last()
-    }    
-    pub fn get_rescale_update(&mut self) -> Option<f32>
+        let mut commands = Vec::new();
+        loop
+        {
+            match self.rx_command.try_recv()
+            {
+                Ok(command)                     => commands.push(command),
+                Err(TryRecvError::Empty)        => break,
+                Err(TryRecvError::Disconnected) =>
+                {
+                    commands.push(WorkerCommand::Abort);
+                    break;
+                }
+            }
+        }
+        commands
+    }
+    // A failure means the main thread is gone, which the next "receive_commands" turns into an abort
+    pub fn send_event(&self, event: WorkerEvent)
     {
-        self.rx_rescale_update.try_iter().last()
-    }    
-    pub fn get_updated_flip(&mut self) -> Option<Option<RotateFlags>>
+        let _ = self.tx_event.send(event);
+    }
+    pub fn send_progression(&self, progression: Progress)
     {
-        self.rx_flip_update.try_iter().last()
-    }    
-    pub fn send_progression(&self, progression: f32)
+        self.send_event(WorkerEvent::Progress(progression));
+    }
+    pub fn send_position(&self, position: usize)
     {
-        self.tx_progression_to_main.send(progression).expect("Failed sending progression to main!");
+        self.send_event(WorkerEvent::Position(position));
     }
-    pub fn is_not_aborted(&self) -> bool
+    // Returns false if the frame was dropped because the GUI did not fetch the previous ones yet
+    pub fn send_preview_frame(&self, frame: PreviewFrame) -> bool
     {
-        self.rx_abort_signal_from_main.try_recv().is_err()
+        self.tx_preview_frame.try_send(frame).is_ok()
     }
-    // pub fn send_open_status(&self, status: bool)     // Could be useful, maybe not?
-    // {
-    //     self.tx_open_status.send(status).expect("Failed sending progression to main!");
-    // }
 }
 
  
@@ -196,46 +1050,95 @@ impl WorkerThreadAsyncChannels
 pub struct FrameSizeManager
 {
     frame_size: Size_<i32>,
-    rescaled_frame_size: Size_<i32>,
-    rotated_rescaled_frame_size: Size_<i32>,
+    cropped_frame_size: Size_<i32>,
+    rotated_frame_size: Size_<i32>, // cropped + rotated, still at the source scale
+    resized_frame_size: Size_<i32>,
+    edit_frame_size: Size_<i32>,    // resized + padded
     preview_frame_size: Size_<i32>,
     gui_scale: f32,
-    re_scale: f32,
-    rotation: Option<RotateFlags>
+    resize: Resize,
+    padding: Option<Padding>,
+    even_size: bool,
+    rotation: Option<RotateFlags>,
+    free_rotation: FreeRotation,
+    crop: Option<Rect>,
+    mirror: Mirror,
+    color_adjust: ColorAdjust,
 }
 impl FrameSizeManager
 {
-    pub fn new(frame_size: Size_<i32>, rotation: Option<RotateFlags>, gui_scale: f32, re_scale: f32) -> Self
+    pub fn new(frame_size: Size_<i32>, crop: Option<Rect>, rotation: Option<RotateFlags>, gui_scale: f32, resize: Resize) -> Self
     {
         let mut new_sizes = FrameSizeManager 
         { 
             frame_size,
-            rescaled_frame_size:         frame_size, 
-            rotated_rescaled_frame_size: frame_size, 
-            preview_frame_size:          frame_size, 
+            cropped_frame_size: frame_size, 
+            rotated_frame_size: frame_size, 
+            resized_frame_size: frame_size, 
+            edit_frame_size:    frame_size, 
+            preview_frame_size: frame_size, 
             gui_scale,
-            re_scale,
-            rotation
+            resize,
+            padding: None,
+            even_size: false,
+            rotation,
+            free_rotation: FreeRotation::default(),
+            crop: None,
+            mirror: Mirror::default(),
+            color_adjust: ColorAdjust::default(),
         };
-        new_sizes.resize_frame(re_scale); // will also rotate + rescale preview!
+        new_sizes.crop(crop); // will also rotate + rescale + rescale preview!
         new_sizes
     }
-    pub fn resize_frame(&mut self, new_rescale: f32)
+    // The crop is expressed in pixels of the source frame, and is applied before rotating & rescaling
+    pub fn crop(&mut self, crop: Option<Rect>)
     {
-        self.re_scale            = new_rescale;
-        self.rescaled_frame_size = self.frame_size.get_resized(self.re_scale);
+        self.crop               = crop.and_then(|rect| rect.get_clamped(self.frame_size));
+        self.cropped_frame_size = self.crop.map_or(self.frame_size, |rect| rect.size());
         self.rotate(self.rotation);
-    }   
+    }
     pub fn rotate(&mut self, flip: Option<RotateFlags>)
     {
-        self.rotation                    = flip;
-        self.rotated_rescaled_frame_size = self.rescaled_frame_size.get_rotated(flip);
-        self.preview_frame_size          = self.rotated_rescaled_frame_size.get_resized(self.gui_scale);
+        self.rotation           = flip;
+        self.rotated_frame_size = self.cropped_frame_size.get_rotated(flip).get_free_rotated(&self.free_rotation);
+        self.resize_frame(self.resize);
     }     
+    pub fn rotate_freely(&mut self, free_rotation: FreeRotation)
+    {
+        self.free_rotation = free_rotation;
+        self.rotate(self.rotation);
+    }
+    pub fn resize_frame(&mut self, resize: Resize)
+    {
+        self.resize             = resize;
+        self.resized_frame_size = self.resize.get_size(self.rotated_frame_size, self.even_size);
+        self.pad(self.padding);
+    }   
+    pub fn pad(&mut self, padding: Option<Padding>)
+    {
+        self.padding         = padding;
+        self.edit_frame_size = padding.map_or(self.resized_frame_size, |padding| padding.get_size(self.resized_frame_size, self.even_size));
+        self.resize_gui(self.gui_scale);
+    }
+    // Required by some codecs, see "OutputCodec::requires_even_size"
+    pub fn set_even_size(&mut self, even_size: bool)
+    {
+        self.even_size = even_size;
+        self.resize_frame(self.resize);
+    }
+    // Does not change any size
+    pub fn mirror(&mut self, mirror: Mirror)
+    {
+        self.mirror = mirror;
+    }
+    pub fn adjust_colors(&mut self, color_adjust: ColorAdjust)
+    {
+        self.color_adjust = color_adjust.get_clamped();
+    }
     pub fn resize_gui(&mut self, new_gui_scale: f32)
     {
         self.gui_scale          = new_gui_scale;
-        self.preview_frame_size = self.rotated_rescaled_frame_size.get_resized(new_gui_scale);
+        self.preview_frame_size = self.edit_frame_size.get_resized(new_gui_scale);
     }    
     pub fn get_preview(&self) -> Size_<i32>
     {
@@ -243,89 +1146,217 @@ impl FrameSizeManager
     }
     pub fn get_edit(&self) -> Size_<i32>
     {
-        self.rotated_rescaled_frame_size
+        self.edit_frame_size
     }
-    pub fn get_rotation(&self) -> Option<RotateFlags>
+    // Size of the frame after cropping & rotating, before rescaling
+    pub fn get_transformed(&self) -> Size_<i32>
     {
-        self.rotation
+        self.rotated_frame_size
     }
-    pub fn update_gui_size(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) -> bool
+    // Size of the video inside the padding
+    pub fn get_resized(&self) -> Size_<i32>
     {
- 
-        if let Some(new_gui_scale) = worker_channels.get_last_size_update()
-        {
-            self.resize_gui(new_gui_scale);
-            return true;
-        }            
-        false
+        self.resized_frame_size
     }
-    pub fn update_flip(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) -> bool
+    // The resized frame inside the padding of the preview
+    pub fn get_preview_content(&self) -> Size_<i32>
     {
- 
-        if let Some(new_flip) = worker_channels.get_updated_flip()
+        match self.is_padded()
         {
-            self.rotate(new_flip);
-            return true
-        }       
-        false
+            true  =>
+            {
+                // Both are rounded: never larger than the padded preview
+                let content = self.resized_frame_size.get_resized(self.gui_scale);
+                Size_::new(content.width.min(self.preview_frame_size.width), content.height.min(self.preview_frame_size.height))
+            }
+            false => self.preview_frame_size,
+        }
     }
-    pub fn update_rescale(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) -> bool
+    // None if the frame already has the aspect ratio of the padding
+    pub fn get_padding(&self) -> Option<Padding>
     {
- 
-        if let Some(new_scale) = worker_channels.get_rescale_update()
+        self.padding.filter(|_| self.is_padded())
+    }
+    // A normalized point of the padded frame, relative to the video inside (outside of [0, 1] on the padding)
+    pub fn unpad_normalized(&self, point: [f32; 2]) -> [f32; 2]
+    {
+        let (edit, content) = (self.edit_frame_size, self.resized_frame_size);
+        let unpad = |value: f32, edit_length: i32, content_length: i32|
         {
-            self.resize_frame(new_scale);
-            return true;
-        }       
-        false
+            let offset = ((edit_length - content_length) / 2) as f32;
+            (value * edit_length as f32 - offset) / content_length as f32
+        };
+        [unpad(point[0], edit.width, content.width), unpad(point[1], edit.height, content.height)]
+    }
+    pub fn get_rotation(&self) -> Option<RotateFlags>
+    {
+        self.rotation
+    }
+    pub fn get_crop(&self) -> Option<Rect>
+    {
+        self.crop
+    }
+    pub fn get_mirror(&self) -> Mirror
+    {
+        self.mirror
+    }
+    pub fn get_color_adjust(&self) -> ColorAdjust
+    {
+        self.color_adjust
+    }
+    pub fn get_free_rotation(&self) -> FreeRotation
+    {
+        self.free_rotation
+    }
+    // Flag of opencv::imgproc::resize, from the transformed frame to the edited one
+    pub fn get_edit_interpolation(&self) -> i32
+    {
+        self.resize.interpolation.get_flag(self.rotated_frame_size, self.resized_frame_size)
+    }
+    // Flag of opencv::imgproc::resize, for the preview
+    pub fn get_interpolation(&self, src_size: Size_<i32>, dst_size: Size_<i32>) -> i32
+    {
+        self.resize.interpolation.get_flag(src_size, dst_size)
     }
-    pub fn update_from_main(&mut self, worker_channels: &mut WorkerThreadAsyncChannels) -> bool
+    // false if the edited frame is the source frame, only rescaled
+    pub fn is_transformed(&self) -> bool
     {
-        self.update_flip(worker_channels) || self.update_gui_size(worker_channels) || self.update_rescale(worker_channels)
+        self.crop.is_some() || self.rotation.is_some() || self.free_rotation.is_rotated() || self.mirror.is_mirrored() || self.color_adjust.is_adjusted()
+    }
+    pub fn is_rescaled(&self) -> bool
+    {
+        self.resized_frame_size != self.rotated_frame_size
+    }
+    pub fn is_padded(&self) -> bool
+    {
+        self.edit_frame_size != self.resized_frame_size
+    }
+    pub fn apply_edit(&mut self, edit: FrameEdit)
+    {
+        match edit
+        {
+            FrameEdit::Flip(flip)                  => self.rotate(flip),
+            FrameEdit::Resize(resize)              => self.resize_frame(resize),
+            FrameEdit::GuiScale(gui_scale)         => self.resize_gui(gui_scale),
+            FrameEdit::Crop(crop)                  => self.crop(crop),
+            FrameEdit::Mirror(mirror)              => self.mirror(mirror),
+            FrameEdit::FreeRotation(free_rotation) => self.rotate_freely(free_rotation),
+            FrameEdit::Padding(padding)            => self.pad(padding),
+            FrameEdit::ColorAdjust(color_adjust)   => self.adjust_colors(color_adjust),
+        }
     }
 }
 
 
-pub struct HighGuiWindow
+// A preview frame ready to be uploaded as a texture by the GUI
+#[derive(Debug, Default)]
+pub struct PreviewFrame
 {
-    winname: String,
+    pub size: [usize; 2],
+    pub rgb: Vec<u8>,
 }
-impl HighGuiWindow
+impl PreviewFrame
 {
-    pub fn build(winname: String, mode: i32) -> Result<HighGuiWindow,opencv::Error>
+    pub fn from_bgr(frame: &Mat) -> Result<Self, opencv::Error>
     {
-        highgui::named_window(&winname[..], mode)?;
-        Ok(Self { winname })
-    }    
-    pub fn show(&self,  mat: &impl opencv::core::ToInputArray) -> Result<(),opencv::Error>
+        let mut rgb_frame = Mat::default();
+        imgproc::cvt_color_def(frame, &mut rgb_frame, imgproc::COLOR_BGR2RGB)?;
+        let size = [rgb_frame.cols() as usize, rgb_frame.rows() as usize];
+        let rgb  = rgb_frame.data_bytes()?.to_vec();
+        Ok(Self { size, rgb })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn resize(mode: ResizeMode) -> Resize
     {
-        if self.is_open() // window can be closed outside of the API!!
-        {
-            highgui::imshow(self.winname.as_str(), mat)?; 
-        }
-        Ok(())
+        Resize { mode, interpolation: Interpolation::default() }
     }
-    pub fn resize(&mut self, size: opencv::core::Size)-> Result<(),opencv::Error>
+
+    fn padding(aspect: (u32, u32)) -> Padding
     {
-        if self.is_open() // window can be closed outside of the API!
-        {
-            highgui::resize_window_size(self.winname.as_str(), size)?;
-        }
-        Ok(())
+        Padding { aspect, ..Padding::default() }
     }
-    // Trick from https://stackoverflow.com/a/9321629
-    pub fn is_open(&self) -> bool
+
+    #[test]
+    fn resize_keeps_the_aspect_ratio()
     {
-        highgui::get_window_property(self.winname.as_str(), highgui::WindowPropertyFlags::WND_PROP_FULLSCREEN.into()).is_ok()
+        let size = Size_::new(1920, 1080);
+        assert_eq!(resize(ResizeMode::Scale(0.5)).get_size(size, false), Size_::new(960, 540));
+        assert_eq!(resize(ResizeMode::Width(1280)).get_size(size, false), Size_::new(1280, 720));
+        assert_eq!(resize(ResizeMode::Height(480)).get_size(size, false), Size_::new(853, 480));
     }
-}
-impl Drop for HighGuiWindow
-{
-    fn drop(&mut self) 
+
+    #[test]
+    fn resize_rounds_down_to_even_sizes()
     {
-        if self.is_open()
-        {
-            let _ = highgui::destroy_window(self.winname.as_str());
-        }
-    }    
-}
\ No newline at end of file
+        let size = Size_::new(1920, 1080);
+        assert_eq!(resize(ResizeMode::Width(1001)).get_size(size, false), Size_::new(1001, 563));
+        assert_eq!(resize(ResizeMode::Width(1001)).get_size(size, true), Size_::new(1000, 562));
+        assert_eq!(resize(ResizeMode::Height(480)).get_size(size, true), Size_::new(852, 480));
+    }
+
+    #[test]
+    fn resize_fits_very_wide_and_very_tall_frames()
+    {
+        let fit = resize(ResizeMode::Fit(1920, 1080));
+        assert_eq!(fit.get_size(Size_::new(4000, 100), false), Size_::new(1920, 48));
+        assert_eq!(fit.get_size(Size_::new(100, 4000), false), Size_::new(27, 1080));
+        assert_eq!(fit.get_size(Size_::new(100, 4000), true), Size_::new(26, 1080));
+        // Enlarged to reach the target
+        assert_eq!(fit.get_size(Size_::new(640, 480), false), Size_::new(1440, 1080));
+    }
+
+    #[test]
+    fn resize_never_goes_below_the_minimum_size()
+    {
+        let fit = resize(ResizeMode::Fit(1920, 1080));
+        assert_eq!(fit.get_size(Size_::new(10000, 1), false), Size_::new(1920, 1));
+        assert_eq!(fit.get_size(Size_::new(10000, 1), true), Size_::new(1920, 2));
+        assert_eq!(fit.get_size(Size_::new(1, 10000), true), Size_::new(2, 1080));
+        assert_eq!(resize(ResizeMode::Scale(0.0001)).get_size(Size_::new(1920, 1080), false), Size_::new(1, 1));
+        assert_eq!(resize(ResizeMode::Scale(0.0001)).get_size(Size_::new(1920, 1080), true), Size_::new(2, 2));
+    }
+
+    #[test]
+    fn padding_letterboxes_and_pillarboxes()
+    {
+        assert_eq!(padding((16, 9)).get_size(Size_::new(1920, 1080), false), Size_::new(1920, 1080));
+        assert_eq!(padding((9, 16)).get_size(Size_::new(1920, 1080), false), Size_::new(1920, 3413));
+        assert_eq!(padding((16, 9)).get_size(Size_::new(1080, 1920), false), Size_::new(3413, 1920));
+        assert_eq!(padding((1, 1)).get_size(Size_::new(1280, 720), false), Size_::new(1280, 1280));
+    }
+
+    #[test]
+    fn padding_rounds_up_to_even_sizes()
+    {
+        assert_eq!(padding((9, 16)).get_size(Size_::new(1920, 1080), true), Size_::new(1920, 3414));
+        assert_eq!(padding((16, 9)).get_size(Size_::new(1919, 1079), true), Size_::new(1920, 1080));
+        assert_eq!(padding((1, 1)).get_size(Size_::new(1, 1), true), Size_::new(2, 2));
+    }
+
+    #[test]
+    fn padding_ignores_an_empty_aspect_ratio()
+    {
+        assert_eq!(padding((0, 9)).get_size(Size_::new(1919, 1079), true), Size_::new(1919, 1079));
+    }
+
+    #[test]
+    fn padding_does_not_overflow_with_extreme_aspect_ratios()
+    {
+        assert_eq!(padding((10_000_000, 1)).get_size(Size_::new(1920, 1080), true), Size_::new(i32::MAX - 1, 1080));
+        assert_eq!(padding((1, 10_000_000)).get_size(Size_::new(1920, 1080), true), Size_::new(1920, i32::MAX - 1));
+    }
+
+    #[test]
+    fn padding_aspect_ratios_are_bounded()
+    {
+        assert!(Padding::is_aspect_valid((1, MAX_ASPECT_VALUE)));
+        assert!(!Padding::is_aspect_valid((0, 9)));
+        assert!(!Padding::is_aspect_valid((MAX_ASPECT_VALUE + 1, 1)));
+    }
+}
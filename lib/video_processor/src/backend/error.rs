@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+use std::sync::mpsc::SendError;
+use crate::backend::WorkerCommand;
+
+// Everything that can go wrong in video_processor: a bad file is reported to the user instead of crashing the app
+#[derive(Debug)]
+pub enum VideoProcessorError
+{
+    OpenFailed(PathBuf),         // missing file, or a format OpenCV cannot decode
+    UnsupportedCodec(String),    // source with an unknown FourCC, or output codec not built into OpenCV
+    WriterFailed(PathBuf),
+    NonUtf8Path(PathBuf),        // OpenCV only takes UTF-8 paths
+    ThreadFailed(String),        // the worker thread panicked, or stopped listening
+    PresetFile(PathBuf, String), // the presets file cannot be read, parsed or written
+    InvalidPreset(String),       // unknown preset name, or unknown id of a codec, direction...
+    OpenCv(opencv::Error),
+}
+
+impl std::fmt::Display for VideoProcessorError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            VideoProcessorError::OpenFailed(path)       => write!(f, "Cannot open video file {}", path.display()),
+            VideoProcessorError::UnsupportedCodec(name) => write!(f, "Unsupported codec: {name}"),
+            VideoProcessorError::WriterFailed(path)     => write!(f, "Cannot write output file {}", path.display()),
+            VideoProcessorError::NonUtf8Path(path)      => write!(f, "Path is not valid UTF-8: {}", path.display()),
+            VideoProcessorError::ThreadFailed(reason)   => write!(f, "Worker thread failed: {reason}"),
+            VideoProcessorError::PresetFile(path, e)    => write!(f, "Presets file {}: {e}", path.display()),
+            VideoProcessorError::InvalidPreset(reason)  => write!(f, "Invalid preset: {reason}"),
+            VideoProcessorError::OpenCv(e)              => write!(f, "OpenCV error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VideoProcessorError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self
+        {
+            VideoProcessorError::OpenCv(e) => Some(e),
+            _                              => None,
+        }
+    }
+}
+
+impl From<opencv::Error> for VideoProcessorError
+{
+    fn from(e: opencv::Error) -> Self
+    {
+        VideoProcessorError::OpenCv(e)
+    }
+}
+
+// The worker drops its receiver when it stops
+impl From<SendError<WorkerCommand>> for VideoProcessorError
+{
+    fn from(_: SendError<WorkerCommand>) -> Self
+    {
+        VideoProcessorError::ThreadFailed(String::from("worker thread stopped receiving commands"))
+    }
+}
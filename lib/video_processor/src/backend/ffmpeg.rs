@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::backend::PlaybackDirection;
+
+// OpenCV's VideoWriter can only write the image stream, with no control over the bitrate.
+// The sound and the bitrate are therefore handled afterwards by calling the ffmpeg command line:
+// the edited (silent) video is written to an intermediate file which is then muxed with
+// the audio stream of the source file, and re-encoded if a bitrate was requested.
+const FFMPEG: &str            = "ffmpeg";
+const INTERMEDIATE_TAG: &str  = ".video_only";
+const MIN_ATEMPO: f64         = 0.5; // range accepted by every version of the "atempo" filter
+const MAX_ATEMPO: f64         = 2_f64;
+
+pub fn is_ffmpeg_available() -> bool
+{
+    Command::new(FFMPEG)
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// "clip_edit.mp4", "mkv" -> "clip_edit.video_only.mkv"
+pub fn get_intermediate_path(edit_file_path: &Path, extension: &str) -> PathBuf
+{
+    let mut file_stem = edit_file_path.file_stem().unwrap_or_default().to_owned();
+    file_stem.push(INTERMEDIATE_TAG);
+
+    let mut intermediate_path = edit_file_path.with_file_name(file_stem);
+    intermediate_path.set_extension(extension);
+    intermediate_path
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AudioSegment
+{
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    pub tempo: f64, // speed factor of the video
+    pub direction: PlaybackDirection,
+}
+
+impl AudioSegment
+{
+    // None if the sound is copied as is
+    fn get_filter(&self) -> Option<String>
+    {
+        let mut filters = Vec::new();
+        match self.direction
+        {
+            PlaybackDirection::Forward   => (),
+            PlaybackDirection::Reverse   => filters.push(String::from("areverse")),
+            PlaybackDirection::Boomerang => filters.push(String::from("asplit[forward][backward];[backward]areverse[reversed];[forward][reversed]concat=n=2:v=0:a=1")),
+        }
+        if self.tempo != 1_f64
+        {
+            filters.push(get_atempo_filter(self.tempo));
+        }
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+}
+
+// "atempo" is chained to reach factors outside of its range, ex: 4.0 -> "atempo=2,atempo=2"
+fn get_atempo_filter(mut tempo: f64) -> String
+{
+    let mut filters = Vec::new();
+    while tempo > MAX_ATEMPO
+    {
+        filters.push(format!("atempo={MAX_ATEMPO}"));
+        tempo /= MAX_ATEMPO;
+    }
+    while tempo < MIN_ATEMPO
+    {
+        filters.push(format!("atempo={MIN_ATEMPO}"));
+        tempo /= MIN_ATEMPO;
+    }
+    filters.push(format!("atempo={tempo:.6}"));
+    filters.join(",")
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VideoEncoding
+{
+    pub encoder: &'static str,
+    pub bitrate_kbps: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PostProcess
+{
+    pub audio: Option<AudioSegment>,         // None: the output has no sound
+    pub audio_codec: &'static str,
+    pub video_encoding: Option<VideoEncoding>, // None: the video stream is copied as is
+}
+
+// Takes the video stream of "intermediate_path" and the audio stream (if any) of "source_path",
+// cut to the audio segment, and writes both into "output_path".
+pub fn run_post_process(source_path: &Path, intermediate_path: &Path, output_path: &Path, post_process: &PostProcess) -> std::io::Result<()>
+{
+    let mut command = Command::new(FFMPEG);
+    command.args(["-y", "-loglevel", "error"])
+        .arg("-i").arg(intermediate_path);
+
+    if let Some(segment) = post_process.audio
+    {
+        command.arg("-ss").arg(format!("{:.3}", segment.start_secs))
+            .arg("-t").arg(format!("{:.3}", segment.duration_secs))
+            .arg("-i").arg(source_path)
+            .args(["-map", "0:v:0", "-map", "1:a?", "-c:a", post_process.audio_codec, "-shortest"]);
+        if let Some(filter) = segment.get_filter()
+        {
+            command.arg("-filter:a").arg(filter);
+        }
+    }
+    else
+    {
+        command.args(["-map", "0:v:0"]);
+    }
+
+    match post_process.video_encoding
+    {
+        Some(encoding) => command.args(["-c:v", encoding.encoder]).arg("-b:v").arg(format!("{}k", encoding.bitrate_kbps)),
+        None           => command.args(["-c:v", "copy"]),
+    };
+
+    let output = command.arg(output_path)
+        .stdout(Stdio::null())
+        .output()?;
+
+    if !output.status.success()
+    {
+        let message = String::from_utf8_lossy(&output.stderr).into_owned();
+        return Err(std::io::Error::other(message));
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use opencv::prelude::*;
+use opencv::videoio::{self, VideoCapture};
+use opencv::core::{Mat, Size};
+use crate::backend::{FrameRange, PlaybackDirection};
+
+// A VideoCapture can only be read forwards. To play a range backwards, it is read by chunks:
+// we seek to the start of the last chunk, read it forwards into memory, then hand out its frames
+// from the last to the first before moving on to the previous chunk. The chunk size is bounded
+// by MAX_CHUNK_BYTES so that long (or 4K) videos do not fill the memory.
+const MAX_CHUNK_BYTES: usize  = 256 * 1024 * 1024;
+const MAX_CHUNK_FRAMES: usize = 64;
+// Seeking with CAP_PROP_POS_FRAMES may land on a nearby keyframe, depending on the codec & backend.
+// When it overshoots, we seek further back and grab the frames up to the requested one.
+const SEEK_BACKOFF_FRAMES: usize = 64;
+
+pub struct FrameReader
+{
+    capture: VideoCapture,
+    direction: PlaybackDirection,
+    frame_range: FrameRange,
+    chunk_frames: usize,
+    is_reversing: bool,
+    forward_position: usize,   // next frame read forwards
+    position: usize,           // last frame handed out
+    reverse_chunk: Vec<Mat>,   // frames of the current chunk, handed out from the back
+    next_chunk_end: usize,     // the previous chunk ends (excluded) where the current one started
+}
+
+impl FrameReader
+{
+    pub fn new(capture: VideoCapture, direction: PlaybackDirection, frame_range: FrameRange, frame_size: Size) -> Result<Self, opencv::Error>
+    {
+        let frame_bytes  = (3 * frame_size.width * frame_size.height).max(1) as usize;
+        let chunk_frames = (MAX_CHUNK_BYTES / frame_bytes).clamp(1, MAX_CHUNK_FRAMES);
+        let (is_reversing, next_chunk_end) = match direction
+        {
+            PlaybackDirection::Reverse   => (true,  frame_range.end),
+            // The last frame is only shown once, when turning back
+            PlaybackDirection::Boomerang => (false, frame_range.end.saturating_sub(1)),
+            PlaybackDirection::Forward   => (false, frame_range.end),
+        };
+        let mut frame_reader = Self
+        {
+            capture,
+            direction,
+            frame_range,
+            chunk_frames,
+            is_reversing,
+            forward_position: frame_range.start,
+            position: frame_range.start,
+            reverse_chunk: Vec::new(),
+            next_chunk_end,
+        };
+        if !is_reversing && frame_range.start > 0
+        {
+            frame_reader.seek_capture(frame_range.start)?;
+        }
+        Ok(frame_reader)
+    }
+    pub fn get_position(&self) -> usize
+    {
+        self.position
+    }
+    // Moves to the source frame "frame" (clamped to the range), returns its index in the frames as played
+    pub fn seek(&mut self, frame: usize) -> Result<usize, opencv::Error>
+    {
+        let frame = frame.min(self.frame_range.end.saturating_sub(1)).max(self.frame_range.start);
+        self.reverse_chunk.clear();
+        self.position = frame;
+        match self.direction
+        {
+            PlaybackDirection::Reverse   =>
+            {
+                // The next chunk is read when the next frame is requested
+                self.next_chunk_end = frame + 1;
+                Ok(self.frame_range.start + (self.frame_range.end - 1 - frame))
+            }
+            // Seeking always goes back to the forward part of the boomerang
+            PlaybackDirection::Boomerang | PlaybackDirection::Forward =>
+            {
+                // A few frames ahead (ex: stepping forward): cheaper to grab them than to seek
+                if !self.is_reversing && (self.forward_position..=self.forward_position + SEEK_BACKOFF_FRAMES).contains(&frame)
+                {
+                    for _ in self.forward_position..frame
+                    {
+                        self.capture.grab()?;
+                    }
+                }
+                else
+                {
+                    self.seek_capture(frame)?;
+                }
+                self.forward_position = frame;
+                self.is_reversing     = false;
+                self.next_chunk_end   = match self.direction
+                {
+                    PlaybackDirection::Boomerang => self.frame_range.end.saturating_sub(1),
+                    _                            => self.frame_range.end,
+                };
+                Ok(frame)
+            }
+        }
+    }
+    fn seek_capture(&mut self, frame: usize) -> Result<(), opencv::Error>
+    {
+        self.capture.set(videoio::CAP_PROP_POS_FRAMES, frame as f64)?;
+        let mut position = self.capture.get(videoio::CAP_PROP_POS_FRAMES)? as usize;
+        if position > frame
+        {
+            self.capture.set(videoio::CAP_PROP_POS_FRAMES, frame.saturating_sub(SEEK_BACKOFF_FRAMES) as f64)?;
+            position = self.capture.get(videoio::CAP_PROP_POS_FRAMES)? as usize;
+            if position > frame
+            {
+                // Still too far: start from the beginning of the file
+                self.capture.set(videoio::CAP_PROP_POS_FRAMES, 0_f64)?;
+                position = 0;
+            }
+        }
+        while position < frame && self.capture.grab()?
+        {
+            position += 1;
+        }
+        Ok(())
+    }
+    pub fn into_capture(self) -> VideoCapture
+    {
+        self.capture
+    }
+    pub fn read(&mut self, frame: &mut Mat) -> Result<bool, opencv::Error>
+    {
+        if !self.is_reversing
+        {
+            let has_frame = self.capture.read(frame)?;
+            self.update_forward_position(has_frame);
+            if has_frame || !self.is_reversing
+            {
+                return Ok(has_frame);
+            }
+        }
+        self.read_reversed(frame)
+    }
+    // Skips a frame without retrieving it (when possible)
+    pub fn grab(&mut self) -> Result<bool, opencv::Error>
+    {
+        if !self.is_reversing
+        {
+            let has_frame = self.capture.grab()?;
+            self.update_forward_position(has_frame);
+            if has_frame || !self.is_reversing
+            {
+                return Ok(has_frame);
+            }
+        }
+        self.read_reversed(&mut Mat::default())
+    }
+    // Boomerang: turns back at the end of the range, or at the real end of the file
+    // since some files report more frames than they contain
+    fn update_forward_position(&mut self, has_frame: bool)
+    {
+        if has_frame
+        {
+            self.position          = self.forward_position;
+            self.forward_position += 1;
+        }
+        if self.direction != PlaybackDirection::Boomerang
+        {
+            return;
+        }
+        if !has_frame
+        {
+            self.next_chunk_end = self.next_chunk_end.min(self.forward_position.saturating_sub(1));
+        }
+        self.is_reversing = !has_frame || self.forward_position >= self.frame_range.end;
+    }
+    fn read_reversed(&mut self, frame: &mut Mat) -> Result<bool, opencv::Error>
+    {
+        if self.reverse_chunk.is_empty() && !self.read_previous_chunk()?
+        {
+            return Ok(false);
+        }
+        *frame        = self.reverse_chunk.pop().expect("Chunk is not empty");
+        self.position = self.next_chunk_end + self.reverse_chunk.len();
+        Ok(true)
+    }
+    fn read_previous_chunk(&mut self) -> Result<bool, opencv::Error>
+    {
+        if self.next_chunk_end <= self.frame_range.start
+        {
+            return Ok(false);
+        }
+        let chunk_start = self.next_chunk_end.saturating_sub(self.chunk_frames).max(self.frame_range.start);
+        self.seek_capture(chunk_start)?;
+        for _ in chunk_start..self.next_chunk_end
+        {
+            let mut frame = Mat::default();
+            if !self.capture.read(&mut frame)?
+            {
+                break;
+            }
+            self.reverse_chunk.push(frame);
+        }
+        self.next_chunk_end = chunk_start;
+        Ok(!self.reverse_chunk.is_empty() || self.read_previous_chunk()?)
+    }
+}
@@ -1,4 +1,5 @@
-use opencv::{core::Size, core::RotateFlags};
+use opencv::{core::Size, core::RotateFlags, core::Rect};
+use crate::backend::{Mirror, FreeRotation, RotationFill};
 use std::ffi::OsString;
 
 pub trait OpenCvRotationCode
@@ -18,17 +19,54 @@ pub fn get_video_name(file_path: &std::path::PathBuf, default: &str) -> String
     let default   = OsString::from(default);
     let file_name = file_path.file_name()
         .unwrap_or(&default)
-        .to_str()
-        .expect("Failed converting OsString to &str");
+        .to_string_lossy();
     String::from(file_name)
 }
 
+// Clockwise, as written in the presets, the projects & on the command line
+pub fn rotation_to_degrees(flip: Option<RotateFlags>) -> u32
+{
+    match flip
+    {
+        None                                          => 0,
+        Some(RotateFlags::ROTATE_90_CLOCKWISE)        => 90,
+        Some(RotateFlags::ROTATE_180)                 => 180,
+        Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE) => 270,
+    }
+}
+
+// None if the rotation is not a multiple of 90° in [0, 270]
+pub fn rotation_from_degrees(degrees: u32) -> Option<Option<RotateFlags>>
+{
+    match degrees
+    {
+        0   => Some(None),
+        90  => Some(Some(RotateFlags::ROTATE_90_CLOCKWISE)),
+        180 => Some(Some(RotateFlags::ROTATE_180)),
+        270 => Some(Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE)),
+        _   => None,
+    }
+}
+
+// "mm:ss", or "h:mm:ss" for an hour and more
+pub fn format_duration(duration: std::time::Duration) -> String
+{
+    let total_secs = duration.as_secs();
+    let (hours, min, sec) = (total_secs / 3600, (total_secs / 60) % 60, total_secs % 60);
+    match hours
+    {
+        0 => format!("{min:02}:{sec:02}"),
+        _ => format!("{hours}:{min:02}:{sec:02}"),
+    }
+}
+
 pub trait SizeEdit
 {
     fn resize(&mut self, scale: Option<f32>) -> &mut Self;
     fn get_resized(&self, scale: f32) -> Self;
     fn rotate(&mut self, rotation: Option<RotateFlags>) -> &mut Self;
     fn get_rotated(&self, rotation: Option<RotateFlags>) -> Self ;
+    fn get_free_rotated(&self, free_rotation: &FreeRotation) -> Self;
 }
 impl SizeEdit for opencv::core::Size
 {
@@ -70,8 +108,88 @@ impl SizeEdit for opencv::core::Size
         new_size.rotate(rotation);
         new_size
     }
+    fn get_free_rotated(&self, free_rotation: &FreeRotation) -> Self
+    {
+        if !free_rotation.is_rotated()
+        {
+            return *self;
+        }
+        let (sin, cos)      = free_rotation.angle.to_radians().sin_cos();
+        let (sin, cos)      = (sin.abs(), cos.abs());
+        let (width, height) = (self.width as f64, self.height as f64);
+        // Bounding box of the rotated frame
+        let bounding_width  = width * cos + height * sin;
+        let bounding_height = width * sin + height * cos;
+        match free_rotation.fill
+        {
+            RotationFill::Expand => Size {width: bounding_width.round() as i32, height: bounding_height.round() as i32},
+            RotationFill::Crop   =>
+            {
+                // The centered rectangle of scale "s" fits if its corners stay inside the rotated frame
+                let scale = (width / bounding_width).min(height / bounding_height);
+                Size {width: ((width * scale) as i32).max(1), height: ((height * scale) as i32).max(1)}
+            }
+            RotationFill::Fill   => *self,
+        }
+    }
     
 }
+pub trait RectEdit
+{
+    fn get_clamped(&self, bounds: Size) -> Option<Self> where Self: Sized;
+}
+impl RectEdit for Rect
+{
+    // Keeps the part of the rectangle inside the frame, None if nothing is left
+    fn get_clamped(&self, bounds: Size) -> Option<Self>
+    {
+        let x0 = self.x.clamp(0, bounds.width);
+        let y0 = self.y.clamp(0, bounds.height);
+        let x1 = (self.x + self.width).clamp(0, bounds.width);
+        let y1 = (self.y + self.height).clamp(0, bounds.height);
+        if x1 <= x0 || y1 <= y0
+        {
+            return None;
+        }
+        Some(Rect::new(x0, y0, x1 - x0, y1 - y0))
+    }
+}
+
+// Converts a point of the displayed (rotated then mirrored) frame into the frame before rotation,
+// both in normalized coordinates (0 = left/top, 1 = right/bottom).
+fn unrotate_normalized(point: [f32; 2], rotation: Option<RotateFlags>, mirror: Mirror) -> [f32; 2]
+{
+    let [u, v] = point;
+    let u      = if mirror.horizontal { 1_f32 - u } else { u };
+    let v      = if mirror.vertical   { 1_f32 - v } else { v };
+    match rotation
+    {
+        None                                          => [u, v],
+        Some(RotateFlags::ROTATE_180)                 => [1_f32 - u, 1_f32 - v],
+        Some(RotateFlags::ROTATE_90_CLOCKWISE)        => [v, 1_f32 - u],
+        Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE) => [1_f32 - v, u],
+    }
+}
+
+// A rectangle selected on the preview (normalized corners, as displayed) is converted
+// into a crop of the source frame. Since the preview shows the current crop,
+// the selection is relative to it: this allows to refine a crop several times.
+pub fn get_crop_from_selection(selection_min: [f32; 2], selection_max: [f32; 2], current_crop: Option<Rect>, frame_size: Size, rotation: Option<RotateFlags>, mirror: Mirror) -> Option<Rect>
+{
+    let current_crop = current_crop.unwrap_or(Rect::new(0, 0, frame_size.width, frame_size.height));
+
+    let corner_a = unrotate_normalized(selection_min, rotation, mirror);
+    let corner_b = unrotate_normalized(selection_max, rotation, mirror);
+    let to_pixel = |value: f32, offset: i32, length: i32| offset + (value.clamp(0_f32, 1_f32) * length as f32).round() as i32;
+
+    let x0 = to_pixel(corner_a[0].min(corner_b[0]), current_crop.x, current_crop.width);
+    let x1 = to_pixel(corner_a[0].max(corner_b[0]), current_crop.x, current_crop.width);
+    let y0 = to_pixel(corner_a[1].min(corner_b[1]), current_crop.y, current_crop.height);
+    let y1 = to_pixel(corner_a[1].max(corner_b[1]), current_crop.y, current_crop.height);
+
+    Rect::new(x0, y0, x1 - x0, y1 - y0).get_clamped(frame_size)
+}
+
 // fourcc = Four Character Code (ex: "DivX", "Xvid", "mp4a")
 // fourcc example [12345678][09876543][32745186][62137854]
 // The get the first byte (= [62137854]), we need to "AND' it with "[00000000][00000000][00000000][11111111]"
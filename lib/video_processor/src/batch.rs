@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+use crate::backend::codec::OutputCodec;
+
+pub const DEFAULT_NAME_TEMPLATE: &str = "{name}_edit";
+const VIDEO_EXTENSIONS: [&str; 10]    = ["mp4", "m4v", "mov", "avi", "mkv", "webm", "wmv", "flv", "mpg", "mpeg"];
+
+pub fn is_video_file(path: &Path) -> bool
+{
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| VIDEO_EXTENSIONS.iter().any(|video| video.eq_ignore_ascii_case(extension)))
+}
+
+// Files are kept as given, folders are replaced by the videos they contain (not recursively), sorted by name
+pub fn collect_video_files(paths: &[PathBuf]) -> Vec<PathBuf>
+{
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths
+    {
+        let mut new_files = match path.is_dir()
+        {
+            false => vec![path.clone()],
+            true  => match std::fs::read_dir(path)
+            {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|file| file.is_file() && is_video_file(file))
+                    .collect(),
+                Err(e)      =>
+                {
+                    log::warn!("Cannot read folder {}: {e}", path.display());
+                    Vec::new()
+                }
+            },
+        };
+        new_files.sort();
+        new_files.retain(|file| !files.contains(file));
+        files.append(&mut new_files);
+    }
+    files
+}
+
+// "{name}" is replaced by the source file name without extension, "{index}" by its position in the batch (from 001).
+// Without "output_dir", the output is written next to its source
+pub fn get_output_path(name_template: &str, source: &Path, index: usize, output_dir: Option<&Path>, codec: OutputCodec) -> PathBuf
+{
+    let name      = source.file_stem().map_or(String::from("video"), |stem| stem.to_string_lossy().into_owned());
+    let file_name = name_template.replace("{name}", &name).replace("{index}", &format!("{:03}", index + 1));
+    let dir       = output_dir.or(source.parent()).unwrap_or(Path::new(""));
+    dir.join(format!("{file_name}.{}", codec.get_extension()))
+}
+
+#[derive(Debug, Default)]
+pub struct BatchReport
+{
+    pub done: Vec<PathBuf>,             // output files
+    pub failed: Vec<(PathBuf, String)>, // source files & reasons
+    pub cancelled: Vec<PathBuf>,        // source files
+}
+
+impl BatchReport
+{
+    pub fn get_summary(&self) -> String
+    {
+        format!("{} exported, {} failed, {} cancelled", self.done.len(), self.failed.len(), self.cancelled.len())
+    }
+    // Summary followed by one line per file
+    pub fn format(&self) -> String
+    {
+        let mut report = format!("Batch finished: {}", self.get_summary());
+        for output in &self.done
+        {
+            report += &format!("\n  done:      {}", output.display());
+        }
+        for (source, reason) in &self.failed
+        {
+            report += &format!("\n  failed:    {} ({reason})", source.display());
+        }
+        for source in &self.cancelled
+        {
+            report += &format!("\n  cancelled: {}", source.display());
+        }
+        report
+    }
+}
@@ -0,0 +1,363 @@
+use std::path::{Path, PathBuf};
+use crate::backend::{create_async_channels, spawn_video_process, MainThreadAsyncChannels, VideoProcJoinHandle};
+use crate::backend::{ProcessOptions, Progress, VideoProcessorError, WorkerCommand, WorkerEvent};
+use crate::{load_video_from_file, PREVIEW_FRAME_BUFFER};
+use crate::batch::{get_output_path, BatchReport};
+
+pub type JobId = usize;
+pub type BatchId = usize;
+pub const MAX_CONCURRENT_JOBS: usize = 8;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum JobStatus
+{
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus
+{
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            JobStatus::Queued    => "Queued",
+            JobStatus::Running   => "Running",
+            JobStatus::Done      => "Done",
+            JobStatus::Failed(_) => "Failed",
+            JobStatus::Cancelled => "Cancelled",
+        }
+    }
+    pub fn is_finished(&self) -> bool
+    {
+        matches!(self, JobStatus::Done | JobStatus::Failed(_) | JobStatus::Cancelled)
+    }
+}
+
+// Errors are returned when joining
+fn log_warnings(job_name: &str, channels: &mut MainThreadAsyncChannels)
+{
+    for message in channels.take_messages()
+    {
+        if let WorkerEvent::Warning(warning) = message
+        {
+            log::warn!("{job_name}: {warning}");
+        }
+    }
+}
+
+// An export of "source_path" with its own capture & worker thread, independent from the VideoProcessor
+#[derive(Debug)]
+pub struct Job
+{
+    pub id: JobId,
+    pub source_path: PathBuf,
+    pub options: ProcessOptions,
+    pub status: JobStatus,
+    pub progress: Progress,
+    pub batch: Option<BatchId>,
+    is_cancelling: bool,
+    channels: Option<MainThreadAsyncChannels>,
+    handle: Option<VideoProcJoinHandle>,
+}
+
+impl Job
+{
+    fn new(id: JobId, source_path: PathBuf, options: ProcessOptions, batch: Option<BatchId>) -> Self
+    {
+        Self
+        {
+            id,
+            source_path,
+            options,
+            status: JobStatus::Queued,
+            progress: Progress::default(),
+            batch,
+            is_cancelling: false,
+            channels: None,
+            handle: None,
+        }
+    }
+    // Name of the output file
+    pub fn get_name(&self) -> String
+    {
+        self.options.get_video_name("output")
+    }
+    fn start(&mut self)
+    {
+        self.progress      = Progress::default();
+        self.is_cancelling = false;
+        self.status        = match self.spawn()
+        {
+            Ok(()) => JobStatus::Running,
+            Err(e) =>
+            {
+                log::error!("{}: {e}", self.get_name());
+                JobStatus::Failed(e.to_string())
+            }
+        };
+    }
+    fn spawn(&mut self) -> Result<(), VideoProcessorError>
+    {
+        let capture = load_video_from_file(&self.source_path)?;
+        let (main_channels, worker_channels) = create_async_channels(PREVIEW_FRAME_BUFFER);
+        self.handle   = Some(spawn_video_process(capture, self.source_path.clone(), self.options.clone(), worker_channels));
+        self.channels = Some(main_channels);
+        Ok(())
+    }
+    // Receives the events of the worker thread, and joins it once finished
+    fn update(&mut self)
+    {
+        let name = self.get_name();
+        if let Some(channels) = self.channels.as_mut()
+        {
+            if let Some(progress) = channels.get_last_progression()
+            {
+                self.progress = progress;
+            }
+            log_warnings(&name, channels);
+        }
+        if let Some(handle) = self.handle.take_if(|handle| handle.is_finished())
+        {
+            self.join(handle);
+        }
+    }
+    fn join(&mut self, handle: VideoProcJoinHandle)
+    {
+        let result = handle.join();
+        // The last events (ex: a failed ffmpeg post-processing) are sent just before the thread ends
+        if let Some(mut channels) = self.channels.take()
+        {
+            log_warnings(&self.get_name(), &mut channels);
+        }
+        self.status = match result
+        {
+            Err(_)                          => JobStatus::Failed(String::from("worker thread panicked")),
+            Ok(Err(e))                      => JobStatus::Failed(e.to_string()),
+            Ok(Ok(_)) if self.is_cancelling => JobStatus::Cancelled,
+            Ok(Ok(_))                       => JobStatus::Done,
+        };
+        match &self.status
+        {
+            JobStatus::Failed(e) => log::error!("{}: {e}", self.get_name()),
+            status               => log::info!("{}: {}", self.get_name(), status.get_name()),
+        }
+    }
+    fn cancel(&mut self)
+    {
+        match self.status
+        {
+            JobStatus::Queued  => self.status = JobStatus::Cancelled,
+            JobStatus::Running =>
+            {
+                self.is_cancelling = true;
+                // A failure means the worker is already stopping
+                if let Some(channels) = self.channels.as_ref()
+                    && channels.send_command(WorkerCommand::Abort).is_err()
+                {
+                    log::debug!("{}: worker already finished", self.get_name());
+                }
+            }
+            _                  => (),
+        }
+    }
+}
+
+// Runs the export jobs in the background, at most "max_concurrent_jobs" at a time (1 = one after the other)
+#[derive(Debug)]
+pub struct JobQueue
+{
+    jobs: Vec<Job>,
+    next_id: JobId,
+    next_batch: BatchId,
+    pub max_concurrent_jobs: usize,
+}
+
+impl Default for JobQueue
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            jobs: Vec::new(),
+            next_id: 0,
+            next_batch: 0,
+            max_concurrent_jobs: 1,
+        }
+    }
+}
+
+impl JobQueue
+{
+    // The output is renamed if a pending job already uses it, see "get_free_output_path"
+    pub fn add_job(&mut self, source_path: PathBuf, options: ProcessOptions) -> JobId
+    {
+        self.push_job(source_path, options, None)
+    }
+    // The same edits for every source, the output names are generated from "name_template" (see "get_output_path").
+    // The trim & the crop are dropped: they are in frames & pixels of another video (like in an ExportPreset)
+    pub fn add_batch(&mut self, sources: &[PathBuf], options: &ProcessOptions, name_template: &str, output_dir: Option<&Path>) -> BatchId
+    {
+        let batch        = self.next_batch;
+        self.next_batch += 1;
+
+        let mut outputs: Vec<PathBuf> = Vec::new();
+        for (index, source) in sources.iter().enumerate()
+        {
+            let mut output = get_output_path(name_template, source, index, output_dir, options.codec);
+            // Never overwrite a source, nor another output of the batch
+            if sources.contains(&output) || outputs.contains(&output)
+            {
+                output = get_output_path(&format!("{name_template}_edit_{{index}}"), source, index, output_dir, options.codec);
+            }
+            let mut job_options        = options.clone();
+            job_options.edit_file_path = output.clone();
+            job_options.start_frame    = None;
+            job_options.end_frame      = None;
+            job_options.crop           = None;
+            self.push_job(source.clone(), job_options, Some(batch));
+            outputs.push(output);
+        }
+        batch
+    }
+    fn push_job(&mut self, source_path: PathBuf, mut options: ProcessOptions, batch: Option<BatchId>) -> JobId
+    {
+        // Jobs always write their output, nobody watches a preview
+        options.should_process = true;
+        options.preview        = false;
+        options.edit_file_path = self.get_free_output_path(&source_path, &options.edit_file_path);
+
+        let id        = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job::new(id, source_path, options, batch));
+        id
+    }
+    // Queued or running jobs reading or writing "path"
+    fn is_path_used(&self, path: &Path) -> bool
+    {
+        self.jobs.iter()
+            .filter(|job| !job.status.is_finished())
+            .any(|job| job.options.edit_file_path == path || job.source_path == path)
+    }
+    // "output_path", renamed "<name>_edit_<index>" if another pending job uses it, so two jobs never write the same file
+    fn get_free_output_path(&self, source_path: &Path, output_path: &Path) -> PathBuf
+    {
+        let name      = output_path.file_stem().map_or(String::from("output"), |stem| stem.to_string_lossy().into_owned());
+        let extension = output_path.extension().map_or(String::new(), |extension| format!(".{}", extension.to_string_lossy()));
+        let mut path  = output_path.to_path_buf();
+        let mut index = 0;
+        while path == source_path || self.is_path_used(&path)
+        {
+            index += 1;
+            path   = output_path.with_file_name(format!("{name}_edit_{index:03}{extension}"));
+        }
+        if index > 0
+        {
+            log::warn!("{} is used by another job, writing {} instead", output_path.display(), path.display());
+        }
+        path
+    }
+    // None while jobs of the batch are still queued or running (or if they were all removed)
+    pub fn get_batch_report(&self, batch: BatchId) -> Option<BatchReport>
+    {
+        let mut jobs = self.jobs.iter().filter(|job| job.batch == Some(batch)).peekable();
+        jobs.peek()?;
+        let mut report = BatchReport::default();
+        for job in jobs
+        {
+            match &job.status
+            {
+                JobStatus::Done           => report.done.push(job.options.edit_file_path.clone()),
+                JobStatus::Failed(reason) => report.failed.push((job.source_path.clone(), reason.clone())),
+                JobStatus::Cancelled      => report.cancelled.push(job.source_path.clone()),
+                JobStatus::Queued | JobStatus::Running => return None,
+            }
+        }
+        Some(report)
+    }
+    pub fn get_jobs(&self) -> &[Job]
+    {
+        &self.jobs
+    }
+    pub fn get_running_count(&self) -> usize
+    {
+        self.jobs.iter().filter(|job| job.status == JobStatus::Running).count()
+    }
+    pub fn has_pending_jobs(&self) -> bool
+    {
+        self.jobs.iter().any(|job| !job.status.is_finished())
+    }
+    // To be called regularly (ex: every GUI frame): follows the running jobs, then starts the queued ones
+    pub fn update(&mut self)
+    {
+        for job in self.jobs.iter_mut().filter(|job| job.status == JobStatus::Running)
+        {
+            job.update();
+        }
+        let max_jobs       = self.max_concurrent_jobs.clamp(1, MAX_CONCURRENT_JOBS);
+        let mut free_slots = max_jobs.saturating_sub(self.get_running_count());
+        for job in self.jobs.iter_mut().filter(|job| job.status == JobStatus::Queued)
+        {
+            if free_slots == 0
+            {
+                break;
+            }
+            job.start();
+            if job.status == JobStatus::Running
+            {
+                free_slots -= 1;
+            }
+        }
+    }
+    // A running job stops at the next frame, its output is kept as is
+    pub fn cancel(&mut self, id: JobId)
+    {
+        if let Some(job) = self.get_job_mut(id)
+        {
+            job.cancel();
+        }
+    }
+    // Failed or cancelled jobs go back to the end of the queue
+    pub fn retry(&mut self, id: JobId)
+    {
+        if let Some(index) = self.jobs.iter().position(|job| job.id == id && matches!(job.status, JobStatus::Failed(_) | JobStatus::Cancelled))
+        {
+            let mut job = self.jobs.remove(index);
+            job.options.edit_file_path = self.get_free_output_path(&job.source_path, &job.options.edit_file_path);
+            job.status                 = JobStatus::Queued;
+            job.progress               = Progress::default();
+            self.jobs.push(job);
+        }
+    }
+    // Running jobs have to be cancelled first
+    pub fn remove(&mut self, id: JobId)
+    {
+        self.jobs.retain(|job| job.id != id || job.status == JobStatus::Running);
+    }
+    pub fn clear_finished(&mut self)
+    {
+        self.jobs.retain(|job| !job.status.is_finished());
+    }
+    fn get_job_mut(&mut self, id: JobId) -> Option<&mut Job>
+    {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+}
+
+impl Drop for JobQueue
+{
+    fn drop(&mut self)
+    {
+        for job in self.jobs.iter_mut()
+        {
+            job.cancel();
+            if let Some(handle) = job.handle.take()
+            {
+                job.join(handle);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 mod backend;
+mod job_queue;
+mod batch;
+mod preset;
 
 use crate::backend::VideoModes;
 pub use crate::backend::get_video_name;
@@ -6,30 +9,53 @@ use crate::backend::process_video_thread;
 use crate::backend::VideoProcThreadPool;
 pub use crate::backend::ProcessOptions;
 pub use crate::backend::VideoInfo;
-use crate::backend::{MainThreadAsyncChannels, WorkerThreadAsyncChannels};
-
-use std::sync::mpsc;
+pub use crate::backend::PreviewFrame;
+pub use crate::backend::Mirror;
+pub use crate::backend::{FreeRotation, RotationFill};
+pub use crate::backend::{Resize, ResizeMode, Interpolation};
+pub use crate::backend::{Padding, PadFill, MAX_ASPECT_VALUE};
+pub use crate::backend::{ColorAdjust, MAX_COLOR_ADJUST, MAX_HUE_SHIFT, MIN_GAMMA, MAX_GAMMA};
+pub use crate::backend::{MIN_SPEED, MAX_SPEED};
+pub use crate::backend::PlaybackDirection;
+pub use crate::backend::SeekTarget;
+pub use crate::backend::Progress;
+pub use crate::job_queue::{JobQueue, Job, JobId, JobStatus, BatchId, MAX_CONCURRENT_JOBS};
+pub use crate::batch::{BatchReport, DEFAULT_NAME_TEMPLATE, collect_video_files, is_video_file};
+pub use crate::preset::{ExportPreset, PresetStore, get_presets_path};
+pub use crate::backend::{WorkerCommand, WorkerEvent, FrameEdit};
+pub use crate::backend::VideoProcessorError;
+use crate::backend::{MainThreadAsyncChannels, create_async_channels};
 
 pub use opencv::core::RotateFlags;
+pub use opencv::core::Rect;
+pub use backend::helper_function::get_crop_from_selection;
+pub use backend::helper_function::{rotation_to_degrees, rotation_from_degrees};
 use opencv::prelude::*;
-use opencv::{videoio, Result};
+use opencv::videoio;
 
 const RESET_PROGRESS:    f32   = 0_f32;
 const GUI_DEFAULT_SCALE: f32   = 1_f32;
-const FRAME_DEFAULT_SCALE: f32 = 1_f32;
+const PREVIEW_FRAME_BUFFER: usize = 2;
 
-pub use backend::helper_function::decode_fourcc;
-use std::sync::mpsc::SendError;
+pub use backend::helper_function::{decode_fourcc, format_duration};
+pub use backend::ffmpeg::is_ffmpeg_available;
+pub use backend::codec::{OutputCodec, probe_supported_codecs};
 #[derive(Debug)]
 pub struct VideoProcessor 
 {
     high_gui_scale: f32,
-    re_scale: f32,
+    resize: Resize,
     file_name: std::path::PathBuf,
     thread_pool: VideoProcThreadPool,
     my_video: Option<videoio::VideoCapture>,
     main_async_channels: Option<MainThreadAsyncChannels>,
     my_flip: Option<RotateFlags>,
+    my_crop: Option<Rect>,
+    my_mirror: Mirror,
+    my_free_rotation: FreeRotation,
+    my_padding: Option<Padding>,
+    my_color_adjust: ColorAdjust,
+    last_messages: Vec<WorkerEvent>, // received when joining the thread
     video_mode: Option<VideoModes>,
     pub video_info: Option<VideoInfo>,
 }
@@ -41,12 +67,18 @@ impl Default for VideoProcessor
         VideoProcessor
         {
             high_gui_scale: GUI_DEFAULT_SCALE,
-            re_scale: FRAME_DEFAULT_SCALE,
+            resize: Resize::default(),
             file_name: std::path::PathBuf::new(),
             thread_pool: VideoProcThreadPool::default(),
             my_video: None,
             main_async_channels: None,
             my_flip: None,
+            my_crop: None,
+            my_mirror: Mirror::default(),
+            my_free_rotation: FreeRotation::default(),
+            my_padding: None,
+            my_color_adjust: ColorAdjust::default(),
+            last_messages: Vec::new(),
             video_mode: None,
             video_info: None, // We do not need the filename, we can let the GUI handle this
         }
@@ -55,46 +87,23 @@ impl Default for VideoProcessor
 
 impl VideoProcessor 
 {
-    fn send_new_gui_size(&self, scale: f32) //-> Result<(), SendError<f32>>
-    {
-        self.main_async_channels.as_ref().inspect(|channels|
-        {
-            channels.send_new_gui_size(scale)
-                    .expect("Could not send resize gui");
-        });
-    }
-    fn send_new_flip(&self, flip: Option<RotateFlags>) //-> Result<(), SendError<f32>>
+    fn send_command(&self, command: WorkerCommand) -> Result<(), VideoProcessorError>
     {
-        self.main_async_channels.as_ref().inspect(|channels|
+        if let Some(channels) = self.main_async_channels.as_ref()
         {
-            channels.send_new_flip(flip)
-                    .expect("Could not send flip");
-        });
-    }
-    fn send_video_mode(&self, video_mode: VideoModes) //-> Result<(), SendError<f32>>
-    {
-        self.main_async_channels.as_ref().inspect(|channels|
-        {
-            channels.send_new_video_mode(video_mode)
-                    .expect("Could not send video_mode");
-        });
+            channels.send_command(command)?;
+        }
+        Ok(())
     }
-    fn send_rescale(&self, rescale: f32) //-> Result<(), SendError<f32>>
+    fn send_edit(&self, edit: FrameEdit) -> Result<(), VideoProcessorError>
     {
-        self.main_async_channels.as_ref().inspect(|channels|
-        {
-            channels.send_new_rescale(rescale)
-                    .expect("Could not send rescale");
-        });
+        self.send_command(WorkerCommand::Edit(edit))
     }
-    pub fn get_current_info(&self) -> Option<VideoInfo> 
+    pub fn get_current_info(&self) -> Result<Option<VideoInfo>, VideoProcessorError> 
     {
-        self.my_video.as_ref().and_then(|capture| 
-        {
-            Some(VideoInfo::new(capture).expect("Cannot construct VideoInfo: OpenCv error"))
-        })
+        self.my_video.as_ref().map(VideoInfo::new).transpose()
     }
-    pub fn unload_video(&mut self) -> Result<bool, opencv::Error>
+    pub fn unload_video(&mut self) -> Result<bool, VideoProcessorError>
     {
         if let Some(mut vid) = self.my_video.take() 
         {
@@ -104,68 +113,124 @@ impl VideoProcessor
         }
         Ok(false)
     }
-    pub fn set_gui_scale(&mut self, scale: f32) -> Result<(), SendError<f32>> 
+    pub fn set_gui_scale(&mut self, scale: f32) -> Result<(), VideoProcessorError> 
     {
-        if self.high_gui_scale != scale && self.has_launched_process() 
+        let has_changed     = self.high_gui_scale != scale;
+        self.high_gui_scale = scale;
+        if has_changed && self.has_launched_process() 
         {
-            self.send_new_gui_size(scale);
+            self.send_edit(FrameEdit::GuiScale(scale))?;
         }
-        self.high_gui_scale = scale;
         Ok(())
     }
-    pub fn set_rescale(&mut self, rescale: f32) -> Result<(), SendError<f32>> 
+    pub fn set_resize(&mut self, resize: Resize) -> Result<(), VideoProcessorError> 
     {
-        if self.re_scale != rescale && self.has_launched_process() 
+        let has_changed = self.resize != resize;
+        self.resize     = resize;
+        if has_changed && self.has_launched_process() 
         {
-            self.send_rescale(rescale);
+            self.send_edit(FrameEdit::Resize(resize))?;
         }
-        self.re_scale = rescale;
         Ok(())
     }
-    pub fn pause_video(&mut self) -> Result<(), SendError<VideoModes>> 
+    pub fn pause_video(&mut self) -> Result<(), VideoProcessorError> 
     {
         if self.has_launched_process() && self.video_mode.as_ref().is_some_and(|mode| *mode == VideoModes::Play) 
         {
             let pause = VideoModes::Pause;
-            self.send_video_mode(pause);
+            self.send_command(WorkerCommand::SetVideoMode(pause))?;
             self.video_mode = Some(pause);
         }
         Ok(())
     }
-    pub fn resume_video(&mut self) -> Result<(), SendError<VideoModes>> 
+    pub fn resume_video(&mut self) -> Result<(), VideoProcessorError> 
     {
         if self.has_launched_process() && self.video_mode.as_ref().is_some_and(|mode| *mode == VideoModes::Pause) 
         {
             let play = VideoModes::Play;
-            self.send_video_mode(play);
+            self.send_command(WorkerCommand::SetVideoMode(play))?;
             self.video_mode = Some(play);
         }
         Ok(())
     }
-    pub fn set_flip(&mut self, flip: Option<RotateFlags>) -> Result<(), SendError<RotateFlags>> 
+    pub fn set_flip(&mut self, flip: Option<RotateFlags>) -> Result<(), VideoProcessorError> 
     {
-        if self.my_flip != flip && self.has_launched_process() 
+        let has_changed = self.my_flip != flip;
+        self.my_flip    = flip;
+        if has_changed && self.has_launched_process() 
         {
-            self.send_new_flip(flip);
+            self.send_edit(FrameEdit::Flip(flip))?;
         }
-        self.my_flip = flip;
         Ok(())
     }
-
-    pub fn try_grab_video(&mut self, file_name: &std::path::PathBuf) -> bool
-    {
-        self.my_video = load_video_from_file(&file_name);
-        println!("try_grab_video: {}", file_name
-                .to_str()
-                .expect("try_grab_video: path-to-str Conversion error")
-        );
-        self.file_name = file_name.clone();
-        if self.has_video() 
+    pub fn set_crop(&mut self, crop: Option<Rect>) -> Result<(), VideoProcessorError> 
+    {
+        let has_changed = self.my_crop != crop;
+        self.my_crop    = crop;
+        if has_changed && self.has_launched_process() 
+        {
+            self.send_edit(FrameEdit::Crop(crop))?;
+        }
+        Ok(())
+    }
+    pub fn set_mirror(&mut self, mirror: Mirror) -> Result<(), VideoProcessorError> 
+    {
+        let has_changed = self.my_mirror != mirror;
+        self.my_mirror  = mirror;
+        if has_changed && self.has_launched_process() 
+        {
+            self.send_edit(FrameEdit::Mirror(mirror))?;
+        }
+        Ok(())
+    }
+    pub fn set_free_rotation(&mut self, free_rotation: FreeRotation) -> Result<(), VideoProcessorError> 
+    {
+        let has_changed       = self.my_free_rotation != free_rotation;
+        self.my_free_rotation = free_rotation;
+        if has_changed && self.has_launched_process() 
+        {
+            self.send_edit(FrameEdit::FreeRotation(free_rotation))?;
+        }
+        Ok(())
+    }
+    pub fn set_padding(&mut self, padding: Option<Padding>) -> Result<(), VideoProcessorError>
+    {
+        let has_changed = self.my_padding != padding;
+        self.my_padding = padding;
+        if has_changed && self.has_launched_process()
+        {
+            self.send_edit(FrameEdit::Padding(padding))?;
+        }
+        Ok(())
+    }
+    pub fn set_color_adjust(&mut self, color_adjust: ColorAdjust) -> Result<(), VideoProcessorError>
+    {
+        let has_changed      = self.my_color_adjust != color_adjust;
+        self.my_color_adjust = color_adjust;
+        if has_changed && self.has_launched_process()
         {
-            println!("try_grab_video: Success, we have the video!");
-            self.video_info = self.get_current_info();
+            self.send_edit(FrameEdit::ColorAdjust(color_adjust))?;
         }
-        self.has_video()
+        Ok(())
+    }
+
+    pub fn try_grab_video(&mut self, file_name: &std::path::PathBuf) -> Result<(), VideoProcessorError>
+    {
+        log::info!("try_grab_video: {}", file_name.display());
+        self.my_video   = None;
+        self.video_info = None;
+        self.file_name  = file_name.clone();
+
+        let capture     = load_video_from_file(file_name)?;
+        self.video_info = Some(VideoInfo::new(&capture)?);
+        self.my_video   = Some(capture);
+        log::info!("try_grab_video: Success, we have the video!");
+        Ok(())
+    }
+    // Path of the loaded video, even while it is used by the worker thread
+    pub fn get_file_path(&self) -> &std::path::Path
+    {
+        &self.file_name
     }
     pub fn has_video(&self) -> bool 
     {
@@ -175,41 +240,13 @@ impl VideoProcessor
     {
         if let Some(capture) = self.my_video.take() 
         {
-            let (tx_progression_to_main,    rx_progression_from_thread) = mpsc::channel();
-            let (tx_abort_signal_to_thread, rx_abort_signal_from_main)  = mpsc::channel();
-            let (tx_video_mode,             rx_video_mode)              = mpsc::channel();
-            let (tx_flip_update,            rx_flip_update)             = mpsc::channel();
-            let (tx_rescale_update,         rx_rescale_update)          = mpsc::channel();
-            // let (tx_open_status,            rx_open_status)             = mpsc::channel();
-            let (tx_highgui_size_update,    rx_highgui_size_update)     = mpsc::channel();
-
-            let main_channels = MainThreadAsyncChannels 
-            {
-                rx_progression_from_thread,
-                tx_abort_signal_to_thread,
-                tx_video_mode,
-                tx_flip_update,
-                tx_rescale_update,
-                // rx_open_status,
-                tx_highgui_size_update,
-            };
-
-            let worker_channels = WorkerThreadAsyncChannels 
-            {
-                tx_progression_to_main,
-                rx_abort_signal_from_main,
-                rx_video_mode,
-                rx_flip_update,
-                rx_rescale_update,
-                // tx_open_status,
-                rx_highgui_size_update,
-            };
+            let (main_channels, worker_channels) = create_async_channels(PREVIEW_FRAME_BUFFER);
 
             self.main_async_channels = Some(main_channels);
             self.video_mode          = Some(VideoModes::Play);
-            process_video_thread(capture, options, &mut self.thread_pool, worker_channels);
+            process_video_thread(capture, self.file_name.clone(), options, &mut self.thread_pool, worker_channels);
 
-            println!(">> App (Main): Move Resouces [video] to worker thread...");
+            log::debug!(">> App (Main): Move Resouces [video] to worker thread...");
             return true;
         }
         false
@@ -218,15 +255,56 @@ impl VideoProcessor
     {
         self.main_async_channels
             .as_ref()
-            .is_some_and(|channels| channels.send_abort_signal().is_ok())
+            .is_some_and(|channels| channels.send_command(WorkerCommand::Abort).is_ok())
 
     }
-    pub fn get_progression(&self) -> Option<f32> 
+    pub fn get_progression(&mut self) -> Option<Progress> 
     {
         self.main_async_channels
-            .as_ref()
+            .as_mut()
             .and_then(|channels| channels.get_last_progression())
     }
+    // Index in the source video of the last frame read by the worker thread
+    pub fn get_position(&mut self) -> Option<usize> 
+    {
+        self.main_async_channels
+            .as_mut()
+            .and_then(|channels| channels.get_last_position())
+    }
+    // Warnings, errors & end of the process reported by the worker thread since the last call,
+    // including the last ones received by "handle_thread_join"
+    pub fn take_worker_messages(&mut self) -> Vec<WorkerEvent> 
+    {
+        let mut messages = std::mem::take(&mut self.last_messages);
+        if let Some(channels) = self.main_async_channels.as_mut()
+        {
+            messages.extend(channels.take_messages());
+        }
+        messages
+    }
+    pub fn seek(&self, target: SeekTarget) -> Result<(), VideoProcessorError> 
+    {
+        self.send_command(WorkerCommand::Seek(target))
+    }
+    pub fn seek_to_frame(&self, frame: usize) -> Result<(), VideoProcessorError> 
+    {
+        self.seek(SeekTarget::Frame(frame))
+    }
+    pub fn seek_to_time(&self, secs: f64) -> Result<(), VideoProcessorError> 
+    {
+        self.seek(SeekTarget::Time(secs))
+    }
+    // Moves by "frames" from the current frame, backwards if negative
+    pub fn step_frames(&self, frames: isize) -> Result<(), VideoProcessorError> 
+    {
+        self.seek(SeekTarget::Step(frames))
+    }
+    pub fn get_preview_frame(&self) -> Option<PreviewFrame> 
+    {
+        self.main_async_channels
+            .as_ref()
+            .and_then(|channels| channels.get_last_preview_frame())
+    }
     pub fn has_launched_process(&self) -> bool 
     {
         !self.thread_pool.is_empty()
@@ -238,43 +316,69 @@ impl VideoProcessor
             .is_some_and(|thread| thread.is_finished())
     }
 
-    pub fn handle_thread_join(&mut self) -> Result<f32, opencv::Error> 
+    // On failure the video is lost with the thread, and has to be opened again
+    pub fn handle_thread_join(&mut self) -> Result<f32, VideoProcessorError> 
     {
-        let mut final_progress = RESET_PROGRESS;
-        if self.has_launched_process() 
+        let mut final_progress = Ok(RESET_PROGRESS);
+        if let Some(thread) = self.thread_pool.pop() 
         {
-            let thread             = self.thread_pool.pop().expect("Threadpool empty.");
-            let mut original_video = thread.join().expect("Failed joining thread!")?;
-
-            let current_frame = original_video.get(videoio::CAP_PROP_POS_FRAMES)? as f32;
-            let frame_count   = original_video.get(videoio::CAP_PROP_FRAME_COUNT)? as f32;
-            final_progress    = current_frame / frame_count;
-
-            original_video.set(videoio::CAP_PROP_POS_AVI_RATIO, 0.)?; // Reset frame count to 0
-            self.my_video = Some(original_video); //.take();
-            println!(">> App (join_thread): Resource returned successfully!");
+            final_progress = thread.join()
+                .map_err(|_| VideoProcessorError::ThreadFailed(String::from("worker thread panicked")))
+                .and_then(|result| result)
+                .and_then(|original_video| self.return_video(original_video));
+        }
+        if final_progress.is_err()
+        {
+            self.video_info = None;
         }
-        self.video_mode          = None;
-        self.main_async_channels = None;
+        // The thread has ended: nothing is sent after these
+        if let Some(mut channels) = self.main_async_channels.take()
+        {
+            self.last_messages.extend(channels.take_messages());
+        }
+        self.video_mode = None;
+        final_progress
+    }
+    fn return_video(&mut self, mut original_video: videoio::VideoCapture) -> Result<f32, VideoProcessorError> 
+    {
+        let current_frame  = original_video.get(videoio::CAP_PROP_POS_FRAMES)? as f32;
+        let frame_count    = original_video.get(videoio::CAP_PROP_FRAME_COUNT)? as f32;
+        let final_progress = current_frame / frame_count;
+
+        original_video.set(videoio::CAP_PROP_POS_AVI_RATIO, 0.)?; // Reset frame count to 0
+        self.my_video = Some(original_video); //.take();
+        log::debug!(">> App (join_thread): Resource returned successfully!");
         Ok(final_progress)
     }
-    pub fn clean_up(&mut self) -> Result<(), opencv::Error> 
+    pub fn clean_up(&mut self) -> Result<(), VideoProcessorError> 
     {
         // println!(">> App: Cleanup: ");
         if self.has_launched_process()
         {
-            println!(">> App (cleanup): !!!! Thread still running: Abort!");
+            log::warn!(">> App (cleanup): !!!! Thread still running: Abort!");
             if self.try_abort() {
-                println!(">> App (cleanup): !!!! Abort msg Successfully sent");
+                log::debug!(">> App (cleanup): !!!! Abort msg Successfully sent");
             } else {
-                panic!("!!!! No transmitter present / Could not send abort signal!");
+                // The worker dropped its receiver: it already stopped
+                log::debug!(">> App (cleanup): !!!! Could not send abort signal, worker thread already finished");
             }
-            self.handle_thread_join()?;
+            let result = self.handle_thread_join();
+            // Nobody reads the messages after the cleanup
+            for message in self.take_worker_messages()
+            {
+                match message
+                {
+                    WorkerEvent::Warning(warning) => log::warn!("{warning}"),
+                    WorkerEvent::Error(error)     => log::error!("{error}"),
+                    _                             => (),
+                }
+            }
+            result?;
         }
 
         if let Some(mut original_vid) = self.my_video.take()
         {
-            println!(">> App (cleanup): Original video released");
+            log::debug!(">> App (cleanup): Original video released");
             original_vid.release()?
         }
         Ok(())
@@ -285,21 +389,25 @@ impl Drop for VideoProcessor
 {
     fn drop(&mut self) 
     {
-        println!("cleanup called");
-        self.clean_up().expect("Video not released correctly");
+        log::debug!("cleanup called");
+        if let Err(e) = self.clean_up()
+        {
+            log::error!("Video not released correctly: {e}");
+        }
     }
 }
 
- pub fn load_video_from_file(file_path: &std::path::PathBuf) -> Option<videoio::VideoCapture>
+ pub fn load_video_from_file(file_path: &std::path::PathBuf) -> Result<videoio::VideoCapture, VideoProcessorError>
 {
-
-    if let Some(file_path) = file_path.to_str() && !file_path.is_empty() // empty string -> passes "0" to c++ API -> uses webcam
+    let file_path_str = file_path.to_str().ok_or_else(|| VideoProcessorError::NonUtf8Path(file_path.clone()))?;
+    if file_path_str.is_empty() // empty string -> passes "0" to c++ API -> uses webcam
     {
-        let video =  videoio::VideoCapture::from_file(file_path, videoio::CAP_ANY).expect("OpenCv Binding error: Failed init video");
-        if video.is_opened().expect("OpenCv Binding error: Cannot check if video is open or not")
-        {
-           return Some(video);
-        }
+        return Err(VideoProcessorError::OpenFailed(file_path.clone()));
     }
-    None
-}
\ No newline at end of file
+    let video = videoio::VideoCapture::from_file(file_path_str, videoio::CAP_ANY)?;
+    if !video.is_opened()?
+    {
+        return Err(VideoProcessorError::OpenFailed(file_path.clone()));
+    }
+    Ok(video)
+}
@@ -0,0 +1,483 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::backend::{ProcessOptions, Mirror, FreeRotation, RotationFill, PlaybackDirection, VideoProcessorError};
+use crate::backend::{Resize, ResizeMode, Interpolation, Padding, PadFill, ColorAdjust};
+use crate::backend::codec::OutputCodec;
+use crate::backend::helper_function::{rotation_to_degrees, rotation_from_degrees};
+
+const CONFIG_DIR_NAME: &str   = "bubbles_video_editor";
+const PRESETS_FILE_NAME: &str = "presets.ron";
+
+// The export options shared by several videos: everything but the paths, the crop & the trim, which depend on the video.
+// The enums are stored by id (as on the command line) and the rotation in degrees, so the file can be written by hand.
+// Missing fields take their default value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ExportPreset
+{
+    pub name: String,
+    pub rotation: u32, // clockwise: 0, 90, 180 or 270
+    pub mirror_horizontal: bool,
+    pub mirror_vertical: bool,
+    pub free_rotation_angle: f64,
+    pub free_rotation_fill: String,
+    pub free_rotation_color: [u8; 3],
+    pub scale: Option<f32>,
+    pub width: Option<i32>,  // with "height": fits within width x height. "scale" is ignored if one of them is set
+    pub height: Option<i32>,
+    pub interpolation: String,
+    pub pad_aspect: Option<(u32, u32)>, // ex: (9, 16), padded to this aspect ratio after the resize
+    pub pad_fill: String,
+    pub pad_color: [u8; 3],
+    pub brightness: f64, // from -100 to 100, like contrast, saturation & temperature
+    pub contrast: f64,
+    pub saturation: f64,
+    pub gamma: f64,
+    pub hue_shift: f64,  // in degrees
+    pub temperature: f64,
+    pub speed: f64,
+    pub blend_frames: bool,
+    pub direction: String,
+    pub codec: String,
+    pub quality: Option<f64>,
+    pub bitrate_kbps: Option<u32>,
+    pub keep_audio: bool,
+}
+
+impl Default for ExportPreset
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            name: String::new(),
+            rotation: 0,
+            mirror_horizontal: false,
+            mirror_vertical: false,
+            free_rotation_angle: 0_f64,
+            free_rotation_fill: String::from(RotationFill::default().get_id()),
+            free_rotation_color: [0, 0, 0],
+            scale: None,
+            width: None,
+            height: None,
+            interpolation: String::from(Interpolation::default().get_id()),
+            pad_aspect: None,
+            pad_fill: String::from(PadFill::default().get_id()),
+            pad_color: [0, 0, 0],
+            brightness: 0_f64,
+            contrast: 0_f64,
+            saturation: 0_f64,
+            gamma: 1_f64,
+            hue_shift: 0_f64,
+            temperature: 0_f64,
+            speed: 1_f64,
+            blend_frames: false,
+            direction: String::from(PlaybackDirection::default().get_id()),
+            codec: String::from(OutputCodec::default().get_id()),
+            quality: None,
+            bitrate_kbps: None,
+            keep_audio: true,
+        }
+    }
+}
+
+impl ExportPreset
+{
+    pub fn from_options(name: &str, options: &ProcessOptions) -> Self
+    {
+        let (scale, width, height) = match options.resize.mode
+        {
+            ResizeMode::Scale(scale)       => ((scale != 1_f32).then_some(scale), None, None),
+            ResizeMode::Width(width)       => (None, Some(width), None),
+            ResizeMode::Height(height)     => (None, None, Some(height)),
+            ResizeMode::Fit(width, height) => (None, Some(width), Some(height)),
+        };
+        let padding = options.padding.unwrap_or_default();
+        Self
+        {
+            name: String::from(name),
+            rotation: rotation_to_degrees(options.flip),
+            mirror_horizontal: options.mirror.horizontal,
+            mirror_vertical: options.mirror.vertical,
+            free_rotation_angle: options.free_rotation.angle,
+            free_rotation_fill: String::from(options.free_rotation.fill.get_id()),
+            free_rotation_color: options.free_rotation.fill_color,
+            scale,
+            width,
+            height,
+            interpolation: String::from(options.resize.interpolation.get_id()),
+            pad_aspect: options.padding.map(|padding| padding.aspect),
+            pad_fill: String::from(padding.fill.get_id()),
+            pad_color: padding.color,
+            brightness: options.color_adjust.brightness,
+            contrast: options.color_adjust.contrast,
+            saturation: options.color_adjust.saturation,
+            gamma: options.color_adjust.gamma,
+            hue_shift: options.color_adjust.hue_shift,
+            temperature: options.color_adjust.temperature,
+            speed: options.speed,
+            blend_frames: options.blend_frames,
+            direction: String::from(options.direction.get_id()),
+            codec: String::from(options.codec.get_id()),
+            quality: options.quality,
+            bitrate_kbps: options.get_bitrate_kbps(),
+            keep_audio: options.keep_audio,
+        }
+    }
+    // Nothing is changed if a value is invalid
+    pub fn apply(&self, options: &mut ProcessOptions) -> Result<(), VideoProcessorError>
+    {
+        let invalid   = |what: &str, value: &str| VideoProcessorError::InvalidPreset(format!("\"{}\" has an invalid {what}: {value}", self.name));
+        let flip      = rotation_from_degrees(self.rotation).ok_or_else(|| invalid("rotation", &self.rotation.to_string()))?;
+        let fill      = RotationFill::from_id(&self.free_rotation_fill).ok_or_else(|| invalid("rotation fill", &self.free_rotation_fill))?;
+        let direction = PlaybackDirection::from_id(&self.direction).ok_or_else(|| invalid("direction", &self.direction))?;
+        let codec     = OutputCodec::from_id(&self.codec).ok_or_else(|| invalid("codec", &self.codec))?;
+        let resize    = self.get_resize().ok_or_else(|| invalid("size", &self.get_size_summary()))?;
+        let pad_fill  = PadFill::from_id(&self.pad_fill).ok_or_else(|| invalid("padding fill", &self.pad_fill))?;
+        let padding   = match self.pad_aspect
+        {
+            Some((width, height)) if !Padding::is_aspect_valid((width, height)) => return Err(invalid("padding aspect ratio", &format!("{width}:{height}"))),
+            Some(aspect)                                                        => Some(Padding { aspect, fill: pad_fill, color: self.pad_color }),
+            None                                                                => None,
+        };
+
+        options.flip          = flip;
+        options.mirror        = Mirror { horizontal: self.mirror_horizontal, vertical: self.mirror_vertical };
+        options.free_rotation = FreeRotation { angle: self.free_rotation_angle, fill, fill_color: self.free_rotation_color };
+        options.resize        = resize;
+        options.padding       = padding;
+        options.color_adjust  = self.get_color_adjust();
+        options.speed         = self.speed.clamp(crate::MIN_SPEED, crate::MAX_SPEED);
+        options.blend_frames  = self.blend_frames;
+        options.direction     = direction;
+        options.codec         = codec;
+        options.quality       = self.quality.map(|quality| quality.clamp(0_f64, 100_f64));
+        options.bitrate_kbps  = self.bitrate_kbps.filter(|_| !codec.is_lossless()); // a lossless codec has no bitrate
+        options.keep_audio    = self.keep_audio;
+        Ok(())
+    }
+    // Out of range values are clamped
+    fn get_color_adjust(&self) -> ColorAdjust
+    {
+        ColorAdjust
+        {
+            brightness: self.brightness,
+            contrast: self.contrast,
+            saturation: self.saturation,
+            gamma: self.gamma,
+            hue_shift: self.hue_shift,
+            temperature: self.temperature,
+        }.get_clamped()
+    }
+    // None if a value is not positive, or the interpolation is unknown
+    fn get_resize(&self) -> Option<Resize>
+    {
+        let mode = match (self.width, self.height)
+        {
+            (Some(width), Some(height)) => ResizeMode::Fit(width, height),
+            (Some(width), None)         => ResizeMode::Width(width),
+            (None, Some(height))        => ResizeMode::Height(height),
+            (None, None)                => ResizeMode::Scale(self.scale.unwrap_or(1_f32)),
+        };
+        let is_valid = match mode
+        {
+            ResizeMode::Scale(scale)                           => scale > 0_f32,
+            ResizeMode::Width(size) | ResizeMode::Height(size) => size > 0,
+            ResizeMode::Fit(width, height)                     => width > 0 && height > 0,
+        };
+        let interpolation = Interpolation::from_id(&self.interpolation)?;
+        is_valid.then_some(Resize { mode, interpolation })
+    }
+    fn get_size_summary(&self) -> String
+    {
+        match (self.width, self.height)
+        {
+            (Some(width), Some(height)) => format!("fit {width}x{height}"),
+            (Some(width), None)         => format!("width {width}"),
+            (None, Some(height))        => format!("height {height}"),
+            (None, None)                => self.scale.map_or(String::new(), |scale| format!("scale {scale}")),
+        }
+    }
+    // Only what differs from the defaults, ex: "h264, 2500 kbit/s"
+    pub fn get_summary(&self) -> String
+    {
+        let default     = ExportPreset::default();
+        let mut summary = vec![self.codec.clone()];
+        if self.rotation != 0
+        {
+            summary.push(format!("rotate {}°", self.rotation));
+        }
+        if self.mirror_horizontal || self.mirror_vertical
+        {
+            summary.push(String::from("mirror"));
+        }
+        if self.free_rotation_angle != 0_f64
+        {
+            summary.push(format!("angle {}° ({})", self.free_rotation_angle, self.free_rotation_fill));
+        }
+        let size_summary = self.get_size_summary();
+        if !size_summary.is_empty()
+        {
+            summary.push(size_summary);
+        }
+        if self.interpolation != default.interpolation
+        {
+            summary.push(format!("{} interpolation", self.interpolation));
+        }
+        if let Some((width, height)) = self.pad_aspect
+        {
+            summary.push(format!("pad {width}:{height} ({})", self.pad_fill));
+        }
+        let colors = [("brightness", self.brightness, default.brightness), ("contrast", self.contrast, default.contrast),
+                      ("saturation", self.saturation, default.saturation), ("gamma", self.gamma, default.gamma),
+                      ("hue", self.hue_shift, default.hue_shift), ("temperature", self.temperature, default.temperature)];
+        for (name, value, default_value) in colors
+        {
+            if value != default_value
+            {
+                summary.push(format!("{name} {value}"));
+            }
+        }
+        if self.speed != default.speed
+        {
+            summary.push(format!("{}x{}", self.speed, if self.blend_frames { " blended" } else { "" }));
+        }
+        if self.direction != default.direction
+        {
+            summary.push(self.direction.clone());
+        }
+        if let Some(quality) = self.quality
+        {
+            summary.push(format!("quality {quality}"));
+        }
+        if let Some(bitrate_kbps) = self.bitrate_kbps
+        {
+            summary.push(format!("{bitrate_kbps} kbit/s"));
+        }
+        if !self.keep_audio
+        {
+            summary.push(String::from("no audio"));
+        }
+        summary.join(", ")
+    }
+}
+
+// Shipped with the app, can be replaced by a user preset of the same name
+fn get_builtin_presets() -> Vec<ExportPreset>
+{
+    let preset = |name: &str| ExportPreset { name: String::from(name), ..ExportPreset::default() };
+    vec![
+        ExportPreset { scale: Some(0.5), ..preset("Half size") },
+        ExportPreset { codec: String::from(OutputCodec::H264.get_id()), width: Some(1920), height: Some(1080), bitrate_kbps: Some(8000), ..preset("YouTube 1080p") },
+        ExportPreset { codec: String::from(OutputCodec::H264.get_id()), width: Some(1280), height: Some(720), bitrate_kbps: Some(2500), ..preset("Web 720p") },
+        ExportPreset { codec: String::from(OutputCodec::H264.get_id()), width: Some(1080), height: Some(1080), pad_aspect: Some((1, 1)), pad_fill: String::from(PadFill::Blur.get_id()), bitrate_kbps: Some(5000), ..preset("Square 1:1") },
+        ExportPreset { codec: String::from(OutputCodec::H264.get_id()), width: Some(1080), height: Some(1920), pad_aspect: Some((9, 16)), pad_fill: String::from(PadFill::Blur.get_id()), bitrate_kbps: Some(8000), ..preset("Vertical 9:16") },
+        ExportPreset { codec: String::from(OutputCodec::Ffv1.get_id()), ..preset("Archive lossless") },
+        ExportPreset { speed: 8_f64, blend_frames: true, keep_audio: false, ..preset("Timelapse 8x") },
+        ExportPreset { direction: String::from(PlaybackDirection::Boomerang.get_id()), keep_audio: false, ..preset("Boomerang") },
+    ]
+}
+
+// %APPDATA% on Windows, ~/Library/Application Support on macOS, $XDG_CONFIG_HOME or ~/.config otherwise
+fn get_config_dir() -> Option<PathBuf>
+{
+    let get_env_path = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let config_dir   = if cfg!(target_os = "windows")
+    {
+        get_env_path("APPDATA")
+    }
+    else if cfg!(target_os = "macos")
+    {
+        get_env_path("HOME").map(|home| home.join("Library").join("Application Support"))
+    }
+    else
+    {
+        get_env_path("XDG_CONFIG_HOME").or_else(|| get_env_path("HOME").map(|home| home.join(".config")))
+    };
+    config_dir.map(|dir| dir.join(CONFIG_DIR_NAME))
+}
+
+// Shared by the editor & the command line. None if the OS gives no config folder
+pub fn get_presets_path() -> Option<PathBuf>
+{
+    get_config_dir().map(|dir| dir.join(PRESETS_FILE_NAME))
+}
+
+// The built-in presets, followed by the ones of the user (saved as RON in "path")
+#[derive(Debug)]
+pub struct PresetStore
+{
+    builtin_presets: Vec<ExportPreset>,
+    user_presets: Vec<ExportPreset>,
+    path: Option<PathBuf>, // None: the user presets are not saved
+}
+
+// Built-in presets only
+impl Default for PresetStore
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            builtin_presets: get_builtin_presets(),
+            user_presets: Vec::new(),
+            path: None,
+        }
+    }
+}
+
+impl PresetStore
+{
+    // A missing file is not an error, it is created by the first saved preset
+    pub fn load(path: Option<PathBuf>) -> Result<Self, VideoProcessorError>
+    {
+        let user_presets = match &path
+        {
+            Some(path) if path.exists() => read_presets(path)?,
+            _                           => Vec::new(),
+        };
+        Ok(Self { user_presets, path, ..Self::default() })
+    }
+    // User presets hide the built-in ones of the same name
+    pub fn get_presets(&self) -> impl Iterator<Item = &ExportPreset>
+    {
+        self.builtin_presets.iter()
+            .filter(|preset| !self.is_user_preset(&preset.name))
+            .chain(self.user_presets.iter())
+    }
+    // Case insensitive
+    pub fn find(&self, name: &str) -> Option<&ExportPreset>
+    {
+        self.get_presets().find(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
+    pub fn is_user_preset(&self, name: &str) -> bool
+    {
+        self.user_presets.iter().any(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
+    pub fn can_save(&self) -> bool
+    {
+        self.path.is_some()
+    }
+    // Replaces the user preset of the same name, then saves the file
+    pub fn set_user_preset(&mut self, preset: ExportPreset) -> Result<(), VideoProcessorError>
+    {
+        match self.user_presets.iter_mut().find(|user_preset| user_preset.name.eq_ignore_ascii_case(&preset.name))
+        {
+            Some(user_preset) => *user_preset = preset,
+            None              => self.user_presets.push(preset),
+        }
+        self.save()
+    }
+    pub fn remove_user_preset(&mut self, name: &str) -> Result<(), VideoProcessorError>
+    {
+        self.user_presets.retain(|preset| !preset.name.eq_ignore_ascii_case(name));
+        self.save()
+    }
+    fn save(&self) -> Result<(), VideoProcessorError>
+    {
+        let Some(path) = &self.path else
+        {
+            return Err(VideoProcessorError::PresetFile(PathBuf::from(PRESETS_FILE_NAME), String::from("no config folder to save it to")));
+        };
+        let presets_error = |e: &dyn std::fmt::Display| VideoProcessorError::PresetFile(path.clone(), e.to_string());
+        if let Some(dir) = path.parent()
+        {
+            std::fs::create_dir_all(dir).map_err(|e| presets_error(&e))?;
+        }
+        let text = ron::ser::to_string_pretty(&self.user_presets, ron::ser::PrettyConfig::default()).map_err(|e| presets_error(&e))?;
+        std::fs::write(path, text).map_err(|e| presets_error(&e))
+    }
+}
+
+fn read_presets(path: &Path) -> Result<Vec<ExportPreset>, VideoProcessorError>
+{
+    let presets_error = |e: &dyn std::fmt::Display| VideoProcessorError::PresetFile(path.to_path_buf(), e.to_string());
+    let text          = std::fs::read_to_string(path).map_err(|e| presets_error(&e))?;
+    ron::from_str(&text).map_err(|e| presets_error(&e))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::backend::MAX_ASPECT_VALUE;
+    use opencv::core::RotateFlags;
+
+    #[test]
+    fn rotation_degrees_round_trip()
+    {
+        for flip in [None, Some(RotateFlags::ROTATE_90_CLOCKWISE), Some(RotateFlags::ROTATE_180), Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE)]
+        {
+            assert_eq!(rotation_from_degrees(rotation_to_degrees(flip)), Some(flip));
+        }
+        assert_eq!(rotation_to_degrees(Some(RotateFlags::ROTATE_90_COUNTERCLOCKWISE)), 270);
+        assert_eq!(rotation_from_degrees(45), None);
+        assert_eq!(rotation_from_degrees(360), None);
+    }
+
+    #[test]
+    fn apply_restores_the_options_of_from_options()
+    {
+        let edited = ProcessOptions
+        {
+            flip: Some(RotateFlags::ROTATE_180),
+            mirror: Mirror { horizontal: true, vertical: false },
+            resize: Resize { mode: ResizeMode::Fit(1280, 720), interpolation: Interpolation::default() },
+            padding: Some(Padding { aspect: (9, 16), fill: PadFill::Blur, color: [10, 20, 30] }),
+            speed: 2_f64,
+            direction: PlaybackDirection::Boomerang,
+            codec: OutputCodec::H264,
+            quality: Some(80_f64),
+            bitrate_kbps: Some(4000),
+            keep_audio: false,
+            ..ProcessOptions::default()
+        };
+        let preset      = ExportPreset::from_options("Test", &edited);
+        let mut options = ProcessOptions::default();
+        assert!(preset.apply(&mut options).is_ok());
+        assert_eq!((options.flip, options.mirror, options.resize, options.padding), (edited.flip, edited.mirror, edited.resize, edited.padding));
+        assert_eq!((options.speed, options.direction, options.codec), (edited.speed, edited.direction, edited.codec));
+        assert_eq!((options.quality, options.bitrate_kbps, options.keep_audio), (edited.quality, edited.bitrate_kbps, edited.keep_audio));
+    }
+
+    #[test]
+    fn apply_changes_nothing_if_a_value_is_invalid()
+    {
+        let mut options = ProcessOptions::default();
+        let preset      = ExportPreset { rotation: 45, scale: Some(0.5), ..ExportPreset::default() };
+        assert!(preset.apply(&mut options).is_err());
+        assert_eq!(options.resize, Resize::default());
+
+        let preset = ExportPreset { codec: String::from("unknown"), scale: Some(0.5), ..ExportPreset::default() };
+        assert!(preset.apply(&mut options).is_err());
+        assert_eq!(options.resize, Resize::default());
+    }
+
+    #[test]
+    fn apply_rejects_out_of_range_aspect_ratios()
+    {
+        let mut options = ProcessOptions::default();
+        for pad_aspect in [(0, 1), (1, MAX_ASPECT_VALUE + 1), (10_000_000, 1)]
+        {
+            let preset = ExportPreset { pad_aspect: Some(pad_aspect), ..ExportPreset::default() };
+            assert!(preset.apply(&mut options).is_err());
+            assert_eq!(options.padding, None);
+        }
+        let preset = ExportPreset { pad_aspect: Some((MAX_ASPECT_VALUE, 1)), ..ExportPreset::default() };
+        assert!(preset.apply(&mut options).is_ok());
+        assert_eq!(options.padding.map(|padding| padding.aspect), Some((MAX_ASPECT_VALUE, 1)));
+    }
+
+    #[test]
+    fn lossless_codecs_drop_the_bitrate()
+    {
+        let mut options = ProcessOptions::default();
+        let preset      = ExportPreset { codec: String::from(OutputCodec::Ffv1.get_id()), bitrate_kbps: Some(4000), ..ExportPreset::default() };
+        assert!(preset.apply(&mut options).is_ok());
+        assert_eq!(options.bitrate_kbps, None);
+
+        let options = ProcessOptions { codec: OutputCodec::Ffv1, bitrate_kbps: Some(4000), ..ProcessOptions::default() };
+        assert_eq!(options.get_bitrate_kbps(), None);
+        assert_eq!(ExportPreset::from_options("Archive", &options).bitrate_kbps, None);
+    }
+}